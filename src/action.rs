@@ -5,6 +5,8 @@ use serde::{
   Deserialize, Serialize,
 };
 
+use crate::colors::{schema::PaletteDocument, Colors};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum Action {
   Tick,
@@ -40,13 +42,38 @@ pub enum Action {
   ToggleSpin,
 
   ToggleHSV,
+  SwitchColorModel,
+  ToggleContrastMetric,
+  ToggleContrastMatrix,
+  ToggleNamedColors,
+  FixContrast,
+  ToggleContrastTarget,
+  SimulateCvd,
+  CycleColorDepth,
 
   ColorUp,
   ColorDown,
+  AddSlot,
+  RemoveSlot,
+  MoveSlotUp,
+  MoveSlotDown,
 
   TogglePalette,
-
-
+  ToggleLock,
+  ToggleRoles,
+  IncreaseValue,
+  DecreaseValue,
+
+  SavePalette,
+  LoadPalette,
+  DeletePalette,
+  RenamePalette,
+  ReadTerminalPalette,
+  QueryTerminalPalette,
+  TerminalPalette(PaletteDocument),
+  ToggleLiveApply,
+  ApplyPalette(Colors),
+  RestorePalette,
 
 }
 
@@ -92,13 +119,35 @@ impl<'de> Deserialize<'de> for Action {
           "SwitchMarker" => Ok(Action::SwitchMarker),
           "ToggleSpin" => Ok(Action::ToggleSpin),
           "HSV" => Ok(Action::ToggleHSV),
+          "ColorModel" | "HSL" => Ok(Action::SwitchColorModel), // HSL is the key of the HSL toggle it replaced
+          "ContrastMetric" => Ok(Action::ToggleContrastMetric),
+          "ContrastMatrix" => Ok(Action::ToggleContrastMatrix),
+          "NamedColors" => Ok(Action::ToggleNamedColors),
+          "FixContrast" => Ok(Action::FixContrast),
+          "ContrastTarget" => Ok(Action::ToggleContrastTarget),
+          "SimulateCVD" => Ok(Action::SimulateCvd),
+          "ColorDepth" => Ok(Action::CycleColorDepth),
           //"HSVPrev" => Ok(Action::HSVPrev),
           //"HSVNext" => Ok(Action::HSVNext),
           //"HSVDecrease" => Ok(Action::HSVDecrease),
           //"HSVIncrease" => Ok(Action::HSVIncrease),
           "ColorUp" => Ok(Action::ColorUp),
           "ColorDown" => Ok(Action::ColorDown),
+          "AddColor" => Ok(Action::AddSlot),
+          "RemoveColor" => Ok(Action::RemoveSlot),
+          "MoveColorUp" => Ok(Action::MoveSlotUp),
+          "MoveColorDown" => Ok(Action::MoveSlotDown),
           "Palette" => Ok(Action::TogglePalette),
+          "Lock" => Ok(Action::ToggleLock),
+          "Roles" => Ok(Action::ToggleRoles),
+          "Increase" => Ok(Action::IncreaseValue),
+          "Decrease" => Ok(Action::DecreaseValue),
+          "SavePalette" => Ok(Action::SavePalette),
+          "LoadPalette" => Ok(Action::LoadPalette),
+          "DeletePalette" => Ok(Action::DeletePalette),
+          "RenamePalette" => Ok(Action::RenamePalette),
+          "TerminalPalette" => Ok(Action::ReadTerminalPalette),
+          "LiveApply" => Ok(Action::ToggleLiveApply),
           //"PaletteNext" => Ok(Action::PaletteNext),
           //"PalettePrev" => Ok(Action::PalettePrev),
           "SelectLeft" => Ok(Action::SelectLeft),
@@ -6,6 +6,7 @@ use tokio::sync::mpsc;
 
 use crate::{
   action::Action,
+  colors::{ansi::ColorDepth, schema::PaletteDocument},
   components::{home::Home, fps::FpsCounter, Component},
   config::Config,
   mode::Mode,
@@ -24,10 +25,16 @@ pub struct App {
 }
 
 impl App {
-  pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
-    let home = Home::new();
-    let fps = FpsCounter::default();
+  pub fn new(tick_rate: f64, frame_rate: f64, palette: Option<PaletteDocument>, color_depth: ColorDepth, terminal_palette: Option<PaletteDocument>) -> Result<Self> {
     let config = Config::new()?;
+    let mut home = Home::new().with_palette_config(&config.palette)?.with_color_depth(color_depth);
+    if let Some(palette) = palette {
+      home = home.with_palette(palette);
+    }
+    if let Some(terminal_palette) = terminal_palette {
+      home = home.with_terminal_palette(terminal_palette);
+    }
+    let fps = FpsCounter::default();
     let mode = Mode::Home;
     Ok(Self {
       tick_rate,
@@ -105,6 +112,14 @@ impl App {
           Action::Quit => self.should_quit = true,
           Action::Suspend => self.should_suspend = true,
           Action::Resume => self.should_suspend = false,
+          Action::QueryTerminalPalette => {
+            match tui.read_palette() {
+              Ok(palette) => action_tx.send(Action::TerminalPalette(palette.to_document()))?,
+              Err(e) => action_tx.send(Action::Error(format!("Could not read the terminal palette: {e}")))?,
+            }
+          },
+          Action::ApplyPalette(ref colors) => tui.apply_palette(colors)?,
+          Action::RestorePalette => tui.restore_palette()?,
           Action::Resize(w, h) => {
             tui.resize(Rect::new(0, 0, w, h))?;
             tui.draw(|f| {
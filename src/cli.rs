@@ -1,11 +1,17 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{builder::{PossibleValue, TypedValueParser}, error::ErrorKind, ArgAction, Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::Result;
 
-use crate::utils::version;
+use crate::{
+  colors::{ansi::ColorDepth, generators::{generate_palette_with_harmony, Harmony, HarmonyParams}, perceptual::{ColorSpace, DeltaE}, schema::PaletteDocument, ColorRGB, Colors, ContrastMetric, MAX_SLOTS, MIN_SLOTS},
+  export::ExportFormat,
+  palettes::PaletteStore,
+  utils::version,
+};
 
 #[derive(Parser, Debug)]
-#[command(author, version = version(), about)]
+#[command(author, version = version(), about, args_conflicts_with_subcommands = true)]
 pub struct Cli {
   #[arg(short, long, value_name = "FLOAT", help = "Tick rate, i.e. number of ticks per second", default_value_t = 1.0)]
   pub tick_rate: f64,
@@ -18,4 +24,194 @@ pub struct Cli {
     default_value_t = 4.0
   )]
   pub frame_rate: f64,
+
+  #[arg(
+    long,
+    value_name = "FILE|NAME",
+    conflicts_with_all = ["base", "colors"],
+    help = "Start with a palette file or a saved palette"
+  )]
+  pub palette: Option<String>,
+
+  #[arg(long, value_name = "COLOR", conflicts_with = "colors", help = "Start with a palette generated from this color")]
+  pub base: Option<ColorRGB>,
+
+  #[arg(long, value_name = "HARMONY", value_parser = HarmonyParser, requires = "base", help = "Harmony used with --base [default: monochromatic]")]
+  pub harmony: Option<Harmony>,
+
+  #[arg(long, value_enum, requires = "base", help = "Color space used with --base [default: hsv]")]
+  pub space: Option<ColorSpace>,
+
+  #[arg(long, value_name = "N", requires = "base", value_parser = parse_size, help = "Number of colors generated with --base [default: 5]")]
+  pub size: Option<usize>,
+
+  #[arg(long, value_enum, help = "Colors the terminal can show, detected from COLORTERM and TERM by default")]
+  pub color_depth: Option<ColorDepth>,
+
+  #[arg(
+    value_name = "COLOR",
+    num_args = MIN_SLOTS..=MAX_SLOTS,
+    action = ArgAction::Set,
+    help = "Start with these colors: background, A, B, C, highlight and any further ones"
+  )]
+  pub colors: Vec<ColorRGB>,
+
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+impl Cli {
+  /// The palette the TUI should start with, if any was requested.
+  pub fn startup_palette(&self) -> Result<Option<PaletteDocument>> {
+    if let Some(palette) = &self.palette {
+      return Ok(Some(PaletteStore::new().resolve(palette)?));
+    }
+    if let Some(base) = &self.base {
+      let harmony = self.harmony.unwrap_or_default();
+      let colors = generate_palette_with_harmony(base.clone(), harmony, self.space.unwrap_or_default(), &HarmonyParams::default().with_count(self.size.unwrap_or(5)));
+      return Ok(Some(PaletteDocument::new("", colors).with_harmony(harmony, base.clone())));
+    }
+    if !self.colors.is_empty() {
+      return Ok(Some(PaletteDocument::new("", Colors::from_colors(self.colors.clone()))));
+    }
+    Ok(None)
+  }
+}
+
+/// Headless commands that print to stdout instead of starting the TUI.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Print a color in other color models
+  Convert {
+    #[arg(value_name = "COLOR", help = "Color as #rrggbb, r,g,b, any CSS color or a color name")]
+    color: ColorRGB,
+    #[arg(long, value_enum, help = "Only print this color model")]
+    to: Option<ColorModel>,
+  },
+  /// Generate a palette from a base color
+  Generate {
+    #[arg(long, value_name = "HARMONY", value_parser = HarmonyParser, default_value_t = Harmony::default(), help = "Harmony used for generation")]
+    harmony: Harmony,
+    #[arg(value_name = "COLOR", help = "Base color as #rrggbb, r,g,b, any CSS color or a color name")]
+    color: ColorRGB,
+    #[arg(long, value_enum, default_value_t = ColorSpace::Hsv, help = "Color space hues and lightness are changed in")]
+    space: ColorSpace,
+    #[arg(long, value_name = "N", default_value_t = 5, value_parser = parse_size, help = "Number of colors")]
+    size: usize,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Hex, help = "Output format")]
+    format: ExportFormat,
+  },
+  /// Print the contrast of a text color on a background color
+  Contrast {
+    #[arg(value_name = "TEXT")]
+    a: ColorRGB,
+    #[arg(value_name = "BACKGROUND")]
+    b: ColorRGB,
+    #[arg(long, value_enum, default_value_t = ContrastMetric::Wcag, help = "Contrast measure")]
+    metric: ContrastMetric,
+  },
+  /// Warn about palette slots that are hard to tell apart, with normal vision and each color vision deficiency
+  Check {
+    #[arg(value_name = "PALETTE", help = "Palette file (.json, .toml, .yaml) or name of a saved palette")]
+    palette: String,
+    #[arg(long, value_enum, default_value_t = DeltaE::Ciede2000, help = "Color difference formula")]
+    metric: DeltaE,
+    #[arg(long, value_name = "FLOAT", help = "Distance below which slots are flagged [default: 5 for ciede2000, 0.05 for ok]")]
+    threshold: Option<f64>,
+  },
+  /// Export a palette file or saved palette to another format
+  Export {
+    #[arg(value_name = "PALETTE", help = "Palette file (.json, .toml, .yaml) or name of a saved palette")]
+    palette: String,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Hex, help = "Output format")]
+    format: ExportFormat,
+    #[arg(long, help = "Export the semantic roles (fg, bg, accent, ...) instead of the slots")]
+    roles: bool,
+  },
+  /// List the palette generators with their parameters
+  Generators,
+}
+
+/// Parses a generator name, the registered generators are listed in the help.
+#[derive(Clone)]
+struct HarmonyParser;
+
+impl TypedValueParser for HarmonyParser {
+  type Value = Harmony;
+
+  fn parse_ref(&self, cmd: &clap::Command, _arg: Option<&clap::Arg>, value: &std::ffi::OsStr) -> Result<Harmony, clap::Error> {
+    let value = value.to_str().ok_or_else(|| clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))?;
+    value.parse().map_err(|e: String| clap::Error::raw(ErrorKind::InvalidValue, format!("{}\n", e)).with_cmd(cmd))
+  }
+
+  fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+    Some(Box::new(Harmony::all().map(|h| PossibleValue::new(h.name()).help(h.generator().description()))))
+  }
+}
+
+/// Number of colors of a generated palette.
+fn parse_size(s: &str) -> Result<usize, String> {
+  let size: usize = s.parse().map_err(|_| format!("invalid number `{}`", s))?;
+  if !(MIN_SLOTS..=MAX_SLOTS).contains(&size) {
+    return Err(format!("a palette has {} to {} colors", MIN_SLOTS, MAX_SLOTS));
+  }
+  Ok(size)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum ColorModel {
+  Hex,
+  Rgb,
+  Hsv,
+  Hsl,
+  Xyz,
+  Lab,
+  Lch,
+  Oklab,
+  Oklch,
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_startup_colors() -> Result<()> {
+    let cli = Cli::try_parse_from(["termcolors", "#202020", "#FFFFFF", "#90485D", "26,97,127", "#48DC03"])?;
+    let doc = cli.startup_palette()?.unwrap();
+    assert_eq!(doc.colors.by_name("color_c"), Some(&ColorRGB::new(26, 97, 127)));
+    let cli = Cli::try_parse_from(["termcolors", "#202020", "#FFFFFF", "#90485D", "#1A617F", "#48DC03", "#000000"])?;
+    let doc = cli.startup_palette()?.unwrap();
+    assert_eq!(doc.colors.len(), 6);
+    assert_eq!(doc.colors.name(5), "color_d");
+    assert!(Cli::try_parse_from(["termcolors", "#202020"]).is_err());
+    let too_many = ["#202020"; MAX_SLOTS + 1];
+    assert!(Cli::try_parse_from(std::iter::once("termcolors").chain(too_many)).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_startup_base() -> Result<()> {
+    let cli = Cli::try_parse_from(["termcolors", "--base", "#00EEEC", "--harmony", "triadic"])?;
+    let doc = cli.startup_palette()?.unwrap();
+    assert_eq!(doc.harmony, Some(Harmony::TRIADIC));
+    assert_eq!(doc.colors, generate_palette_with_harmony(ColorRGB::new(0, 238, 236), Harmony::TRIADIC, ColorSpace::Hsv, &HarmonyParams::default()));
+    let cli = Cli::try_parse_from(["termcolors", "--base", "#00EEEC", "--space", "oklch", "--size", "9"])?;
+    let colors = generate_palette_with_harmony(ColorRGB::new(0, 238, 236), Harmony::default(), ColorSpace::Oklch, &HarmonyParams::default().with_count(9));
+    assert_eq!(cli.startup_palette()?.unwrap().colors, colors);
+    assert!(Cli::try_parse_from(["termcolors", "--base", "#00EEEC", "--size", "1"]).is_err());
+    assert!(Cli::try_parse_from(["termcolors", "--size", "8"]).is_err());
+    assert!(Cli::try_parse_from(["termcolors", "--harmony", "triadic"]).is_err());
+    assert!(Cli::try_parse_from(["termcolors", "--palette", "dusk", "--base", "#00EEEC"]).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_no_startup_palette() -> Result<()> {
+    let cli = Cli::try_parse_from(["termcolors", "--tick-rate", "2"])?;
+    assert!(cli.startup_palette()?.is_none());
+    Ok(())
+  }
 }
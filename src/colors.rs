@@ -1,32 +1,346 @@
 
 use ratatui::prelude::Color;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::{str::FromStr, fmt::Error};
 
+use perceptual::DeltaE;
 
+
+pub mod ansi;
+pub mod css;
 pub mod generators;
+pub mod names;
+pub mod perceptual;
+pub mod roles;
+pub mod schema;
+
+
+/// Fewest and most slots a palette can have.
+pub const MIN_SLOTS: usize = 2;
+pub const MAX_SLOTS: usize = 32;
+
+/// Names of the slots of the classic five color palette, generated palettes are named after them.
+pub const DEFAULT_SLOT_NAMES: [&str; 5] = ["background", "color_a", "color_b", "color_c", "highlight"];
+
+/// A named color of a palette.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct Slot {
+  pub name: String,
+  pub color: ColorRGB,
+}
 
+impl Slot {
+  pub fn new(name: &str, color: ColorRGB) -> Self {
+    Slot { name: name.to_string(), color }
+  }
+}
 
-#[derive(Default, Clone)]
+/// Ordered, named slots of a palette. The first slot is the background the others are shown on.
+///
+/// Serialized as a map from slot name to hex string, in slot order.
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Colors {
-  pub background: ColorRGB,
-  pub color_a: ColorRGB,
-  pub color_b: ColorRGB,
-  pub color_c: ColorRGB,
-  pub highlight: ColorRGB,
+  slots: Vec<Slot>,
 }
 
-#[derive(Default, Clone, PartialEq, Eq, Debug)]
+impl Default for Colors {
+  fn default() -> Self {
+    Colors::from_colors(vec![ColorRGB::default(); DEFAULT_SLOT_NAMES.len()])
+  }
+}
+
+impl Colors {
+  /// Checks the number of slots and that the names are unique; a slot named `background` is moved to the front.
+  pub fn new(mut slots: Vec<Slot>) -> Result<Self, String> {
+    if slots.len() < MIN_SLOTS || slots.len() > MAX_SLOTS {
+      return Err(format!("a palette has {} to {} colors, not {}", MIN_SLOTS, MAX_SLOTS, slots.len()));
+    }
+    for (i, slot) in slots.iter().enumerate() {
+      if slot.name.trim().is_empty() {
+        return Err("a palette color has an empty name".to_string());
+      }
+      if slots[..i].iter().any(|s| s.name == slot.name) {
+        return Err(format!("the color name `{}` is used twice", slot.name));
+      }
+    }
+    if let Some(idx) = slots.iter().position(|s| s.name == "background") {
+      let background = slots.remove(idx);
+      slots.insert(0, background);
+    }
+    Ok(Colors { slots })
+  }
+
+  /// Slots with the default names: the classic five, then `color_d`, `color_e`, ... Takes at least two colors.
+  pub fn from_colors(colors: Vec<ColorRGB>) -> Self {
+    let mut this = Colors { slots: vec![] };
+    for color in colors {
+      let name = match DEFAULT_SLOT_NAMES.get(this.slots.len()) {
+        Some(name) => {name.to_string()},
+        None => {this.next_free_name()},
+      };
+      this.slots.push(Slot { name, color });
+    }
+    this
+  }
+
+  pub fn len(&self) -> usize {
+    self.slots.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.slots.is_empty()
+  }
+
+  pub fn slots(&self) -> &[Slot] {
+    &self.slots
+  }
+
+  pub fn name(&self, idx: usize) -> &str {
+    &self.slots[idx].name
+  }
+
+  pub fn color(&self, idx: usize) -> &ColorRGB {
+    &self.slots[idx].color
+  }
+
+  pub fn background(&self) -> &ColorRGB {
+    &self.slots[0].color
+  }
+
+  /// The slots shown on the background, all but the first.
+  pub fn foregrounds(&self) -> impl Iterator<Item = &ColorRGB> + Clone {
+    self.slots[1..].iter().map(|s| &s.color)
+  }
+
+  pub fn by_name(&self, name: &str) -> Option<&ColorRGB> {
+    self.slots.iter().find(|s| s.name == name).map(|s| &s.color)
+  }
+
+  /// Color the UI marks selections with: the `highlight` slot, or the last one.
+  pub fn accent(&self) -> &ColorRGB {
+    self.by_name(self.accent_name()).unwrap_or(&self.slots[self.slots.len() - 1].color)
+  }
+
+  /// Name of the [`Colors::accent`] slot.
+  pub fn accent_name(&self) -> &str {
+    match self.by_name("highlight") {
+      Some(_) => {"highlight"},
+      None => {&self.slots[self.slots.len() - 1].name},
+    }
+  }
+
+  pub fn colors(&self) -> impl Iterator<Item = &ColorRGB> {
+    self.slots.iter().map(|s| &s.color)
+  }
+
+  /// The palette with the slot at `idx` changed to `color`.
+  pub fn with_color(&self, idx: usize, color: ColorRGB) -> Colors {
+    let mut colors = self.clone();
+    colors.slots[idx].color = color;
+    colors
+  }
+
+  /// Every color changed by `f`, the names are kept.
+  pub fn map(&self, f: impl Fn(&ColorRGB) -> ColorRGB) -> Colors {
+    Colors { slots: self.slots.iter().map(|s| Slot { name: s.name.clone(), color: f(&s.color) }).collect() }
+  }
+
+  /// These names with the colors of `colors`, slot by slot; slots missing in `colors` keep their color,
+  /// further colors of `colors` are added with the next free names.
+  pub fn recolored(&self, colors: &Colors) -> Colors {
+    let mut recolored = self.clone();
+    for (slot, color) in recolored.slots.iter_mut().zip(colors.colors()) {
+      slot.color = color.clone();
+    }
+    for color in colors.colors().skip(recolored.len()) {
+      let name = recolored.next_free_name();
+      recolored.slots.push(Slot { name, color: color.clone() });
+    }
+    recolored
+  }
+
+  /// Drops the slots after the first `len`, at least [`MIN_SLOTS`] stay. Returns the dropped slots.
+  pub fn truncate(&mut self, len: usize) -> Vec<Slot> {
+    self.slots.split_off(len.clamp(MIN_SLOTS, self.slots.len()))
+  }
+
+  /// First unused name of `color_a` to `color_z`, then `color_27`, `color_28`, ...
+  pub fn next_free_name(&self) -> String {
+    ('a'..='z')
+      .map(|c| format!("color_{}", c))
+      .chain((27..).map(|n| format!("color_{}", n)))
+      .find(|name| self.by_name(name).is_none())
+      .expect("the names are endless")
+  }
+
+  /// Inserts a slot with the next free name at `idx`, after the background.
+  pub fn insert(&mut self, idx: usize, color: ColorRGB) -> Result<(), String> {
+    if self.slots.len() >= MAX_SLOTS {
+      return Err(format!("a palette has at most {} colors", MAX_SLOTS));
+    }
+    let name = self.next_free_name();
+    self.slots.insert(idx.clamp(1, self.slots.len()), Slot { name, color });
+    Ok(())
+  }
+
+  /// Removes the slot at `idx`, the background and the last foreground stay.
+  pub fn remove(&mut self, idx: usize) -> Result<Slot, String> {
+    if idx == 0 {
+      return Err("the background cannot be removed".to_string());
+    }
+    if self.slots.len() <= MIN_SLOTS {
+      return Err(format!("a palette has at least {} colors", MIN_SLOTS));
+    }
+    Ok(self.slots.remove(idx))
+  }
+
+  /// Moves the slot at `from` with its name to `to`, the background stays first.
+  pub fn move_slot(&mut self, from: usize, to: usize) -> Result<(), String> {
+    if from == 0 || to == 0 {
+      return Err("the background stays the first color".to_string());
+    }
+    if from >= self.slots.len() {
+      return Err(format!("there is no color {} to move", from));
+    }
+    let slot = self.slots.remove(from);
+    self.slots.insert(to.min(self.slots.len()), slot);
+    Ok(())
+  }
+
+  /// Swaps the colors of two slots, the names stay in place.
+  pub fn swap_colors(&mut self, a: usize, b: usize) {
+    let color = self.slots[a].color.clone();
+    self.slots[a].color = std::mem::replace(&mut self.slots[b].color, color);
+  }
+
+  /// The palette as it is displayed: translucent colors composited over the background,
+  /// and a translucent background over black.
+  pub fn composited(&self) -> Colors {
+    let background = self.background().over(&ColorRGB::new(0, 0, 0));
+    let mut colors = self.map(|c| c.over(&background));
+    colors.slots[0].color = background;
+    colors
+  }
+
+  /// The palette as seen with a color vision deficiency.
+  pub fn simulate_cvd(&self, cvd: Cvd) -> Colors {
+    self.map(|c| c.simulate_cvd(cvd))
+  }
+
+  /// Pairs of slots closer than `threshold`, with their distance.
+  pub fn near_duplicates(&self, metric: DeltaE, threshold: f64) -> Vec<(&str, &str, f64)> {
+    let slots = self.named_slots();
+    let mut pairs = vec![];
+    for (i, (name_x, x)) in slots.iter().enumerate() {
+      for (name_y, y) in slots.iter().skip(i + 1) {
+        let distance = metric.distance(x, y);
+        if distance < threshold {
+          pairs.push((*name_x, *name_y, distance));
+        }
+      }
+    }
+    pairs
+  }
+
+  /// Slots in display order, with their names.
+  pub fn named_slots(&self) -> Vec<(&str, &ColorRGB)> {
+    self.slots.iter().map(|s| (s.name.as_str(), &s.color)).collect()
+  }
+}
+
+impl Serialize for Colors {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    use serde::ser::SerializeMap;
+    let mut map = serializer.serialize_map(Some(self.slots.len()))?;
+    for slot in &self.slots {
+      map.serialize_entry(&slot.name, &slot.color)?;
+    }
+    map.end()
+  }
+}
+
+impl<'de> Deserialize<'de> for Colors {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    Colors::new(deserialize_slots(deserializer)?).map_err(de::Error::custom)
+  }
+}
+
+/// Reads a map from slot name to color, keeping the order of the document.
+pub fn deserialize_slots<'de, D>(deserializer: D) -> Result<Vec<Slot>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  struct SlotsVisitor;
+
+  impl<'de> de::Visitor<'de> for SlotsVisitor {
+    type Value = Vec<Slot>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+      formatter.write_str("a map from color name to color")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Vec<Slot>, A::Error>
+    where
+      A: de::MapAccess<'de>,
+    {
+      let mut slots = vec![];
+      while let Some((name, color)) = map.next_entry::<String, ColorRGB>()? {
+        slots.push(Slot { name, color });
+      }
+      Ok(slots)
+    }
+  }
+
+  deserializer.deserialize_map(SlotsVisitor)
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ColorRGB {
   pub color: Color,
   pub r: u8,
   pub g: u8,
   pub b: u8,
+  /// Opacity, 255 is fully opaque.
+  pub a: u8,
+}
+
+impl Default for ColorRGB {
+  fn default() -> Self {
+    ColorRGB { color: Color::default(), r: 0, g: 0, b: 0, a: u8::MAX }
+  }
 }
 
 impl ColorRGB {
   pub fn new(r: u8, g: u8, b: u8) -> Self {
+    Self::from_rgba(r, g, b, u8::MAX)
+  }
+
+  pub fn from_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
     let color = Color::Rgb(r, g, b);
-    ColorRGB { color, r, g, b }
+    ColorRGB { color, r, g, b, a }
+  }
+
+  /// The same color with a different opacity.
+  pub fn with_alpha(&self, a: u8) -> Self {
+    Self::from_rgba(self.r, self.g, self.b, a)
+  }
+
+  pub fn is_opaque(&self) -> bool {
+    self.a == u8::MAX
+  }
+
+  /// Composites this color over `background` (source-over in sRGB), the result is opaque.
+  /// The background is treated as opaque.
+  pub fn over(&self, background: &ColorRGB) -> ColorRGB {
+    let alpha = self.a as f64 / 255.0;
+    let blend = |fg: u8, bg: u8| (fg as f64 * alpha + bg as f64 * (1.0 - alpha)).round() as u8;
+    ColorRGB::new(blend(self.r, background.r), blend(self.g, background.g), blend(self.b, background.b))
   }
   /// Creates a shaded variant of the passed color.
   /// frac is clamped between -1 and 1 -> -100% brightness to +100% brightness.
@@ -46,36 +360,40 @@ impl ColorRGB {
     }
   }
 
-  /// Creates a new ColorRGB from a hex string, returns Error on invalid input.
-  /// TODO: Include all ratatui colors that dont return a valid hex representation
+  /// Creates a new ColorRGB from a `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` hex string or a color name
+  /// (which covers the named ratatui colors), returns Error on invalid input.
   pub fn from_hex(s: &str) -> Result<Self, Error> { 
     match s
     .to_lowercase()
     .replace([' ', '-', '_'], "")
-    .as_ref()
+    .as_str()
     {
-      "white" => {Ok(Self::new(255,255,255))},
-      "lightblue" => {Ok(Self::new(48,48,240))},
-      _ => { if let (Ok(r), Ok(g), Ok(b)) = {
-        if !s.starts_with('#') || s.len() != 7 {
-            return Err(Error);
-        }
-          (
-              u8::from_str_radix(&s[1..3], 16),
-              u8::from_str_radix(&s[3..5], 16),
-              u8::from_str_radix(&s[5..7], 16),
-          )
-        } {
-            Ok(Self::new(r, g, b))
-        } else {
-            return Err(Error);
+      name if !name.starts_with('#') => {names::lookup(name).map(|named| named.color.clone()).ok_or(Error)},
+      _ => {
+        let digits = s.strip_prefix('#').ok_or(Error)?;
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+          return Err(Error);
         }
-
+        // short forms repeat each digit, #f80 is #ff8800
+        let channels: Vec<u8> = match digits.len() {
+          3 | 4 => digits.chars().map(|c| c.to_digit(16).unwrap() as u8 * 0x11).collect(),
+          6 | 8 => (0..digits.len()).step_by(2).map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap()).collect(),
+          _ => {return Err(Error);},
+        };
+        Ok(Self::from_rgba(channels[0], channels[1], channels[2], channels.get(3).copied().unwrap_or(u8::MAX)))
       }
     }
 
   }
 
+  /// Returns the color as an uppercase `#RRGGBB` string, or `#RRGGBBAA` if it is translucent.
+  pub fn to_hex(&self) -> String {
+    if !self.is_opaque() {
+      return format!("#{:02X}{:02X}{:02X}{:02X}", self.r, self.g, self.b, self.a);
+    }
+    format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+  }
+
   /// Creates a new ColorRGB from a ratatui Color via its hex representation 
   /// 
   /// Will return Error for colors that don't return a Hex !
@@ -94,7 +412,7 @@ impl ColorRGB {
     let r = u8::MAX - self.r;
     let g = u8::MAX - self.g;
     let b = u8::MAX - self.b; 
-    ColorRGB { color: Color::Rgb(r, g, b), r, g, b }
+    ColorRGB { color: Color::Rgb(r, g, b), r, g, b, a: self.a }
 
   }
 
@@ -136,17 +454,8 @@ impl ColorRGB {
     };
 
     // 6. is different due to needing to convert to degrees
-    let hue = if delta.abs() < f64::EPSILON {
-        0.0
-    } else if c_max == r {
-        60.0 * ((g - b) / delta % 6.0)
-    } else if c_max == g {
-        60.0 * ((b - r) / delta + 2.0)
-    } else {
-        60.0 * ((r - g) / delta + 4.0)
-    };
+    let hue = hue_from_rgb(r, g, b, c_max, delta);
 
-    
     round_hsv((hue, saturation, value))
 
   }
@@ -164,68 +473,196 @@ impl ColorRGB {
 
     let c = value * saturation;
 
-    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = rgb_from_hue_chroma(hue, c);
 
     let m = value - c;
 
-    let (r, g, b) = if hue < 60.0 {
-        (c, x, 0.0)
-    } else if hue < 120.0 {
-        (x, c, 0.0)
-    } else if hue < 180.0 {
-        (0.0, c, x)
-    } else if hue < 240.0 {
-        (0.0, x, c)
-    } else if hue < 300.0 {
-        (x, 0.0, c)
+    ColorRGB::new(((r + m) * 255.0) as u8, ((g + m) * 255.0) as u8, ((b + m) * 255.0) as u8)
+  }
+
+  /// Forward conversion from RGB to HSL, hue in degrees, saturation and lightness between 0. and 1.
+  /// 1. L is the midrange of the largest (M) and smallest (m) component: L = (M + m) / 2
+  /// 2. S is the chroma d = M - m relative to the largest chroma possible at that lightness:
+  ///    S = d / (1 - |2L - 1|)
+  /// 3. H is the same hue as in HSV.
+  ///
+  /// Ref: https://en.wikipedia.org/wiki/HSL_and_HSV#From_RGB
+  pub fn rgb_to_hsl(&self) -> (f64, f64, f64) {
+    let r = f64::from(self.r) / 255.0;
+    let g = f64::from(self.g) / 255.0;
+    let b = f64::from(self.b) / 255.0;
+    let c_max = r.max(g).max(b);
+    let c_min = r.min(g).min(b);
+    let delta = c_max - c_min;
+
+    // 1.
+    let lightness = (c_max + c_min) / 2.0;
+    // 2.
+    let saturation = if delta.abs() < f64::EPSILON {
+      0.0
     } else {
-        (c, 0.0, x)
+      delta / (1.0 - (2.0 * lightness - 1.0).abs())
     };
+    // 3.
+    let hue = hue_from_rgb(r, g, b, c_max, delta);
 
-    ColorRGB {
-        color: Color::Rgb( ((r + m) * 255.0) as u8,
-                            ((g + m) * 255.0) as u8,
-                            ((b + m) * 255.0) as u8),
-        r: ((r + m) * 255.0) as u8,
-        g: ((g + m) * 255.0) as u8,
-        b: ((b + m) * 255.0) as u8,
-    }
+    round_hsv((hue, saturation, lightness))
+  }
+
+  /// Ref: https://en.wikipedia.org/wiki/HSL_and_HSV#HSL_to_RGB
+  ///
+  /// Lossy !
+  pub fn from_hsl(hsl: (f64, f64, f64)) -> Self {
+    let (hue, saturation, lightness) = round_hsv(hsl);
+
+    let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+
+    let (r, g, b) = rgb_from_hue_chroma(hue, c);
+
+    let m = lightness - c / 2.0;
+
+    ColorRGB::new(unit_to_channel(r + m), unit_to_channel(g + m), unit_to_channel(b + m))
   }
 
 
 
   pub fn with_hue(&self, new_hue: f64) -> Self {
     let (_, s, v) = self.rgb_to_hsv();
-    ColorRGB::from_hsv((new_hue, s, v))
+    ColorRGB::from_hsv((new_hue, s, v)).with_alpha(self.a)
   }
 
   pub fn with_saturation(&self, new_saturation: f64) -> Self {
     let (h, _, v) = self.rgb_to_hsv();
-    ColorRGB::from_hsv((h, new_saturation, v))
+    ColorRGB::from_hsv((h, new_saturation, v)).with_alpha(self.a)
   }
 
   pub fn with_value(&self, new_value: f64) -> Self {
     let (h, s, _) = self.rgb_to_hsv();
-    ColorRGB::from_hsv((h, s, new_value))
+    ColorRGB::from_hsv((h, s, new_value)).with_alpha(self.a)
   }
 
   pub fn shift_hue(&self, amount: f64) -> Self {
     let (h, s, v) = self.rgb_to_hsv();
-    ColorRGB::from_hsv(((h + amount) % 360.0, s, v))
+    ColorRGB::from_hsv(((h + amount) % 360.0, s, v)).with_alpha(self.a)
   }
 
   pub fn shift_saturation(&self, amount: f64) -> Self {
     let (h, s, v) = self.rgb_to_hsv();
-    ColorRGB::from_hsv((h, (s + amount).min(1.0).max(0.0), v))
+    ColorRGB::from_hsv((h, (s + amount).clamp(0.0, 1.0), v)).with_alpha(self.a)
   }
 
   pub fn shift_value(&self, amount: f64) -> Self {
     let (h, s, v) = self.rgb_to_hsv();
-    ColorRGB::from_hsv((h, s, (v + amount).min(1.0).max(0.0)))
+    ColorRGB::from_hsv((h, s, (v + amount).clamp(0.0, 1.0))).with_alpha(self.a)
+  }
+
+  pub fn with_hsl_saturation(&self, new_saturation: f64) -> Self {
+    let (h, _, l) = self.rgb_to_hsl();
+    ColorRGB::from_hsl((h, new_saturation, l)).with_alpha(self.a)
+  }
+
+  pub fn with_lightness(&self, new_lightness: f64) -> Self {
+    let (h, s, _) = self.rgb_to_hsl();
+    ColorRGB::from_hsl((h, s, new_lightness)).with_alpha(self.a)
+  }
+
+  pub fn shift_hsl_saturation(&self, amount: f64) -> Self {
+    let (h, s, l) = self.rgb_to_hsl();
+    ColorRGB::from_hsl((h, (s + amount).clamp(0.0, 1.0), l)).with_alpha(self.a)
+  }
+
+  pub fn shift_lightness(&self, amount: f64) -> Self {
+    let (h, s, l) = self.rgb_to_hsl();
+    ColorRGB::from_hsl((h, s, (l + amount).clamp(0.0, 1.0))).with_alpha(self.a)
   }
 
 }
 
+impl FromStr for ColorRGB {
+  type Err = String;
+
+  /// Parses a bare `r,g,b` list, a hex code with or without `#`, or any CSS color.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    if s.contains('(') || s.starts_with('#') {
+      return css::parse_css_color(s);
+    }
+    if s.contains(',') {
+      let channels: Vec<&str> = s.split(',').map(|c| c.trim()).collect();
+      if channels.len() != 3 {
+        return Err(format!("expected three channels in `{}`", s));
+      }
+      let mut rgb = [0u8; 3];
+      for (i, channel) in channels.iter().enumerate() {
+        rgb[i] = channel.parse::<u8>().map_err(|_| format!("invalid channel `{}` in `{}`, expected 0-255", channel, s))?;
+      }
+      return Ok(ColorRGB::new(rgb[0], rgb[1], rgb[2]));
+    }
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit()) {
+      return css::parse_css_color(&format!("#{}", s));
+    }
+    css::parse_css_color(s)
+  }
+}
+
+/// Serialized as its `#RRGGBB` hex string, `#RRGGBBAA` if translucent.
+impl Serialize for ColorRGB {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.serialize_str(&self.to_hex())
+  }
+}
+
+impl<'de> Deserialize<'de> for ColorRGB {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let s = String::deserialize(deserializer)?;
+    ColorRGB::from_hex(&s).map_err(|_| de::Error::custom(format!("invalid color `{}`, expected #RRGGBB, #RRGGBBAA or a color name", s)))
+  }
+}
+
+/// Hue in degrees (0 to 360) shared by the HSV and HSL conversions.
+fn hue_from_rgb(r: f64, g: f64, b: f64, c_max: f64, delta: f64) -> f64 {
+  let hue = if delta.abs() < f64::EPSILON {
+    0.0
+  } else if c_max == r {
+    60.0 * ((g - b) / delta % 6.0)
+  } else if c_max == g {
+    60.0 * ((b - r) / delta + 2.0)
+  } else {
+    60.0 * ((r - g) / delta + 4.0)
+  };
+  if hue < 0.0 { hue + 360.0 } else { hue }
+}
+
+/// RGB components between 0. and c for a hue in degrees and chroma c, before adding the lightness offset.
+fn rgb_from_hue_chroma(hue: f64, c: f64) -> (f64, f64, f64) {
+  let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+
+  if hue < 60.0 {
+    (c, x, 0.0)
+  } else if hue < 120.0 {
+    (x, c, 0.0)
+  } else if hue < 180.0 {
+    (0.0, c, x)
+  } else if hue < 240.0 {
+    (0.0, x, c)
+  } else if hue < 300.0 {
+    (x, 0.0, c)
+  } else {
+    (c, 0.0, x)
+  }
+}
+
+/// Maps a channel between 0. and 1. to 0 - 255, rounding to the nearest value.
+fn unit_to_channel(x: f64) -> u8 {
+  (x * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
 /// rounds hue to nearest integer and saturation / value to three decimal places
 pub fn round_hsv(hsv: (f64, f64, f64)) -> (f64, f64, f64) {
   let mut h = hsv.0;
@@ -246,34 +683,267 @@ pub fn round_hsv(hsv: (f64, f64, f64)) -> (f64, f64, f64) {
 }
 
 
-fn normalize_chan(x: u8) -> f32 {
-    x as f32 / u8::MAX as f32
+/// Relative luminance of a color between 0. and 1. as defined by WCAG 2.x.
+///
+/// Ref: https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+pub fn relative_luminance(x: &ColorRGB) -> f64 {
+    let (r, g, b) = x.to_linear();
+    r * 0.2126 + g * 0.7152 + b * 0.0722
+}
+
+/// WCAG 2.x contrast ratio between two colors, from 1. (no contrast) to 21. (black on white).
+/// The order of the colors does not matter.
+///
+/// Ref: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+pub fn contrast_ratio(x: &ColorRGB, y: &ColorRGB) -> f64 {
+    let lx = relative_luminance(x);
+    let ly = relative_luminance(y);
+    (lx.max(ly) + 0.05) / (lx.min(ly) + 0.05)
+}
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WcagLevel {
+    #[default]
+    AA,
+    AAA,
+}
+
+impl WcagLevel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WcagLevel::AA => "AA",
+            WcagLevel::AAA => "AAA",
+        }
+    }
+}
+
+/// Large text is at least 18pt, or 14pt bold.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TextSize {
+    Normal,
+    Large,
+}
+
+/// Minimum contrast ratio for a conformance level and text size.
+pub fn wcag_threshold(level: WcagLevel, size: TextSize) -> f64 {
+    match (level, size) {
+        (WcagLevel::AA, TextSize::Normal) => 4.5,
+        (WcagLevel::AA, TextSize::Large) => 3.0,
+        (WcagLevel::AAA, TextSize::Normal) => 7.0,
+        (WcagLevel::AAA, TextSize::Large) => 4.5,
+    }
+}
+
+pub fn passes_wcag(ratio: f64, level: WcagLevel, size: TextSize) -> bool {
+    ratio >= wcag_threshold(level, size)
+}
+
+/// Best conformance a contrast ratio reaches, from failing even large text to AAA for normal text.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum WcagGrade {
+    Fail,
+    AALarge,
+    AA,
+    AAA,
+}
+
+impl WcagGrade {
+    pub fn from_ratio(ratio: f64) -> Self {
+        if passes_wcag(ratio, WcagLevel::AAA, TextSize::Normal) {
+            WcagGrade::AAA
+        } else if passes_wcag(ratio, WcagLevel::AA, TextSize::Normal) {
+            WcagGrade::AA
+        } else if passes_wcag(ratio, WcagLevel::AA, TextSize::Large) {
+            WcagGrade::AALarge
+        } else {
+            WcagGrade::Fail
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WcagGrade::Fail => "Fail",
+            WcagGrade::AALarge => "AA Large",
+            WcagGrade::AA => "AA",
+            WcagGrade::AAA => "AAA",
+        }
+    }
 }
-fn linearize_chan(x:f32) -> f32 {
-    x.powf(2.2)
+
+impl std::fmt::Display for WcagGrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
-/// returns the colors luminance between 0. and 1.
-pub fn get_luminance(x: &ColorRGB) -> f32 {
-    let _r = linearize_chan(normalize_chan(x.r));
-    let _g = linearize_chan(normalize_chan(x.g));
-    let _b = linearize_chan(normalize_chan(x.b));
+/// Grades a pair of colors, e.g. text on a background.
+pub fn wcag_grade(x: &ColorRGB, y: &ColorRGB) -> WcagGrade {
+    WcagGrade::from_ratio(contrast_ratio(x, y))
+}
+
+/// Color vision deficiencies that can be simulated, each at full severity.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum Cvd {
+    /// No working L cones (red)
+    Protanopia,
+    /// No working M cones (green)
+    Deuteranopia,
+    /// No working S cones (blue)
+    Tritanopia,
+    /// No color vision at all, only luminance
+    Achromatopsia,
+}
+
+impl Cvd {
+    pub const ALL: [Cvd; 4] = [Cvd::Protanopia, Cvd::Deuteranopia, Cvd::Tritanopia, Cvd::Achromatopsia];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cvd::Protanopia => "protanopia",
+            Cvd::Deuteranopia => "deuteranopia",
+            Cvd::Tritanopia => "tritanopia",
+            Cvd::Achromatopsia => "achromatopsia",
+        }
+    }
+
+    /// Machado et al. (2009) matrices for severity 1.0, applied to linear sRGB.
+    ///
+    /// Ref: https://www.inf.ufrgs.br/~oliveira/pubs_files/CVD_Simulation/CVD_Simulation.html
+    fn matrix(&self) -> [[f64; 3]; 3] {
+        match self {
+            Cvd::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Cvd::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Cvd::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+            Cvd::Achromatopsia => [[0.2126, 0.7152, 0.0722]; 3],
+        }
+    }
+}
 
-    _r * 0.2126 + _g * 0.7152 + _b * 0.0722
+impl ColorRGB {
+    /// The color as seen with a color vision deficiency.
+    pub fn simulate_cvd(&self, cvd: Cvd) -> ColorRGB {
+        let (r, g, b) = self.to_linear();
+        let [m0, m1, m2] = cvd.matrix();
+        let apply = |m: [f64; 3]| m[0] * r + m[1] * g + m[2] * b;
+        ColorRGB::from_linear((apply(m0), apply(m1), apply(m2))).with_alpha(self.a)
+    }
 }
 
-/// get the contrast between two colors
-pub fn get_contrast(x: &ColorRGB, y: &ColorRGB) -> f32 {
-    let lx = get_luminance(x);
-    let ly = get_luminance(y);
+/// Screen luminance as estimated by APCA, with the soft clamp for near black.
+fn apca_luminance(x: &ColorRGB) -> f64 {
+    const BLACK_THRESHOLD: f64 = 0.022;
+    const BLACK_CLAMP: f64 = 1.414;
+    let chan = |c: u8| (f64::from(c) / 255.0).powf(2.4);
+    let y = 0.2126729 * chan(x.r) + 0.7151522 * chan(x.g) + 0.0721750 * chan(x.b);
+    if y < BLACK_THRESHOLD {
+        y + (BLACK_THRESHOLD - y).powf(BLACK_CLAMP)
+    } else {
+        y
+    }
+}
 
-    if lx < ly {
-        // x luminance is lower, so it is darker
-        (ly- lx) / (ly + 0.1)
+/// APCA (WCAG 3 draft) lightness contrast Lc of text on a background, roughly between -108. and 106.
+/// Positive for dark text on a light background, negative for light text on a dark background,
+/// so swapping the colors changes the value and not only its sign.
+///
+/// Ref: https://github.com/Myndex/apca-w3 (0.0.98G-4g constants)
+pub fn apca_contrast(text: &ColorRGB, background: &ColorRGB) -> f64 {
+    const DELTA_Y_MIN: f64 = 0.0005;
+    const SCALE: f64 = 1.14;
+    const LO_CLIP: f64 = 0.1;
+    const LO_OFFSET: f64 = 0.027;
+
+    let y_text = apca_luminance(text);
+    let y_bg = apca_luminance(background);
+    if (y_bg - y_text).abs() < DELTA_Y_MIN {
+        return 0.0;
+    }
+    let lc = if y_bg > y_text {
+        // dark text on a light background
+        let sapc = (y_bg.powf(0.56) - y_text.powf(0.57)) * SCALE;
+        if sapc < LO_CLIP {0.0} else {sapc - LO_OFFSET}
     } else {
-        (lx- ly) / (lx + 0.1)
+        // light text on a dark background
+        let sapc = (y_bg.powf(0.65) - y_text.powf(0.62)) * SCALE;
+        if sapc > -LO_CLIP {0.0} else {sapc + LO_OFFSET}
+    };
+    lc * 100.0
+}
+
+/// Use an APCA Lc is sufficient for, after the APCA bronze level lookup:
+/// body text (75), content text (60), large text (45) and spot text or non-text (30).
+pub fn apca_rating(lc: f64) -> &'static str {
+    match lc.abs() {
+        x if x >= 75.0 => "Body",
+        x if x >= 60.0 => "Content",
+        x if x >= 45.0 => "Large",
+        x if x >= 30.0 => "Spot",
+        _ => "Fail",
+    }
+}
+
+/// Contrast measure shown in the UI.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum ContrastMetric {
+    /// WCAG 2 contrast ratio
+    #[default]
+    Wcag,
+    /// APCA lightness contrast (WCAG 3 draft)
+    Apca,
+}
+
+impl ContrastMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContrastMetric::Wcag => "WCAG 2",
+            ContrastMetric::Apca => "APCA",
+        }
+    }
+
+    /// Contrast of text on a background, a ratio for WCAG 2 and Lc for APCA.
+    pub fn contrast(&self, text: &ColorRGB, background: &ColorRGB) -> f64 {
+        match self {
+            ContrastMetric::Wcag => contrast_ratio(text, background),
+            ContrastMetric::Apca => apca_contrast(text, background),
+        }
+    }
+
+    /// Whether the contrast is too low for any kind of text.
+    pub fn is_fail(&self, value: f64) -> bool {
+        match self {
+            ContrastMetric::Wcag => WcagGrade::from_ratio(value) == WcagGrade::Fail,
+            ContrastMetric::Apca => apca_rating(value) == "Fail",
+        }
+    }
+
+    /// Whether the contrast suffices for normal UI text: WCAG 2 AA or APCA content text.
+    pub fn passes_text(&self, value: f64) -> bool {
+        match self {
+            ContrastMetric::Wcag => passes_wcag(value, WcagLevel::AA, TextSize::Normal),
+            ContrastMetric::Apca => value.abs() >= 60.0,
+        }
+    }
+
+    /// Contrast with its grade or rating in 16 columns, e.g. ` 4.52:1 AA     ` or `Lc  63.1 Content`.
+    pub fn describe(&self, text: &ColorRGB, background: &ColorRGB) -> String {
+        let value = self.contrast(text, background);
+        match self {
+            ContrastMetric::Wcag => format!("{:>5.2}:1 {:<8}", value, WcagGrade::from_ratio(value)),
+            ContrastMetric::Apca => format!("Lc{:>6.1} {:<7}", value, apca_rating(value)),
+        }
     }
-    // https://stackoverflow.com/questions/56198778/what-is-the-efficient-way-to-calculate-human-eye-contrast-difference-for-rgb-val/56200738#56200738   
 }
 
 
@@ -425,6 +1095,91 @@ mod tests {
     assert_eq!(rgb, _rgb);
   }
 
+  #[test]
+  fn test_output_hsl_from_rgb() {
+    // https://www.rapidtables.com/convert/color/rgb-to-hsl.html
+    // hex: #00EEEC
+    // rgb: 0, 238, 236
+    // hsl: 179° 100% 46.7%
+
+    let color = ColorRGB::new(0, 238, 236);
+    let hsl = color.rgb_to_hsl();
+
+    assert_eq!(hsl, (179.0, 1.0, 0.467));
+  }
+
+  #[test]
+  fn test_output_rgb_from_hsl() {
+    // https://www.rapidtables.com/convert/color/hsl-to-rgb.html
+    // hsl: 340° 31% 43.1%
+    // rgb: 144, 76, 98
+
+    let color = ColorRGB::from_hsl((340.0, 0.31, 0.431));
+    assert_eq!((color.r, color.g, color.b), (144, 76, 99));
+  }
+
+  #[test]
+  fn test_parse_rgb_hsl_rgb() {
+    // hsl: 20° 60% 50%
+    let rgb: (u8, u8, u8) = (204, 102, 51);
+    let color = ColorRGB::new(rgb.0, rgb.1, rgb.2);
+    let hsl = color.rgb_to_hsl();
+    let _color = ColorRGB::from_hsl(hsl);
+    let _rgb = (_color.r, _color.g, _color.b);
+
+    assert_eq!(rgb, _rgb);
+  }
+
+  #[test]
+  fn test_parse_hsl_rgb_hsl() {
+    let hsl = (20.0, 0.6, 0.5);
+    let color = ColorRGB::from_hsl(hsl);
+
+    assert_eq!(color.rgb_to_hsl(), hsl);
+  }
+
+  #[test]
+  fn test_hsl_helpers() {
+    let color = ColorRGB::new(144, 76, 98);
+    assert_eq!(color.with_lightness(1.0), ColorRGB::new(255, 255, 255));
+    assert_eq!(color.with_lightness(0.0), ColorRGB::new(0, 0, 0));
+    assert_eq!(color.with_hsl_saturation(0.0).rgb_to_hsl().1, 0.0);
+    assert_eq!(color.shift_lightness(2.0), ColorRGB::new(255, 255, 255));
+  }
+
+  #[test]
+  fn test_from_str() {
+    let color = ColorRGB::new(0, 238, 236);
+    assert_eq!(ColorRGB::from_str("#00EEEC"), Ok(color.clone()));
+    assert_eq!(ColorRGB::from_str("00eeec"), Ok(color.clone()));
+    assert_eq!(ColorRGB::from_str("0, 238, 236"), Ok(color.clone()));
+    assert_eq!(ColorRGB::from_str("rgb(0,238,236)"), Ok(color.clone()));
+    assert!(ColorRGB::from_str("0, 256, 236").is_err());
+    assert!(ColorRGB::from_str("#00EEE").is_err());
+    assert_eq!(ColorRGB::from_str("#00EEEC80"), Ok(color.with_alpha(0x80)));
+  }
+
+  #[test]
+  fn test_from_hex_alpha() {
+    assert_eq!(ColorRGB::from_hex("#0EE"), Ok(ColorRGB::new(0, 238, 238)));
+    assert_eq!(ColorRGB::from_hex("#0EE8"), Ok(ColorRGB::from_rgba(0, 238, 238, 0x88)));
+    assert_eq!(ColorRGB::from_hex("#00eeec80"), Ok(ColorRGB::from_rgba(0, 238, 236, 0x80)));
+    assert!(ColorRGB::from_hex("#00EEEC8").is_err());
+    assert!(ColorRGB::from_hex("#00EEEG").is_err());
+    assert_eq!(ColorRGB::from_rgba(0, 238, 236, 0x80).to_hex(), "#00EEEC80");
+    assert_eq!(ColorRGB::from_rgba(0, 238, 236, 0xFF).to_hex(), "#00EEEC");
+    let translucent: ColorRGB = serde_json::from_str("\"#00EEEC80\"").unwrap();
+    assert_eq!(serde_json::to_string(&translucent).unwrap(), "\"#00EEEC80\"");
+  }
+
+  #[test]
+  fn test_over() {
+    let background = ColorRGB::new(32, 32, 32);
+    assert_eq!(ColorRGB::from_rgba(255, 255, 255, 128).over(&background), ColorRGB::new(144, 144, 144));
+    assert_eq!(ColorRGB::from_rgba(255, 0, 0, 0).over(&background), background);
+    assert_eq!(ColorRGB::new(255, 0, 0).over(&background), ColorRGB::new(255, 0, 0));
+  }
+
   #[test]
   fn test_parse_rgb_hsv_rgb() {
     let rgb: (u8, u8, u8) = (144, 76, 98);
@@ -436,6 +1191,151 @@ mod tests {
     assert_eq!(rgb, _rgb);
   }  
 
+  #[test]
+  fn test_contrast_ratio() {
+    // https://webaim.org/resources/contrastchecker/
+    let black = ColorRGB::new(0, 0, 0);
+    let white = ColorRGB::new(255, 255, 255);
+    assert_eq!(contrast_ratio(&black, &white), 21.0);
+    assert_eq!(contrast_ratio(&white, &black), 21.0);
+    assert_eq!(contrast_ratio(&white, &white), 1.0);
+    assert_eq!(format!("{:.2}", contrast_ratio(&ColorRGB::new(119, 119, 119), &white)), "4.48");
+    assert_eq!(format!("{:.2}", contrast_ratio(&ColorRGB::new(0, 0, 255), &white)), "8.59");
+  }
+
+  #[test]
+  fn test_wcag_grade() {
+    assert_eq!(WcagGrade::from_ratio(21.0), WcagGrade::AAA);
+    assert_eq!(WcagGrade::from_ratio(4.5), WcagGrade::AA);
+    assert_eq!(WcagGrade::from_ratio(4.48), WcagGrade::AALarge);
+    assert_eq!(WcagGrade::from_ratio(2.9), WcagGrade::Fail);
+    assert!(passes_wcag(4.5, WcagLevel::AAA, TextSize::Large));
+    assert!(!passes_wcag(4.5, WcagLevel::AAA, TextSize::Normal));
+    let grey = ColorRGB::new(118, 118, 118);
+    assert_eq!(wcag_grade(&grey, &ColorRGB::new(255, 255, 255)), WcagGrade::AA);
+  }
+
+  #[test]
+  fn test_apca_contrast() {
+    // https://github.com/Myndex/apca-w3#readme
+    let white = ColorRGB::new(255, 255, 255);
+    let grey = ColorRGB::new(136, 136, 136);
+    let black = ColorRGB::new(0, 0, 0);
+    let light_grey = ColorRGB::new(170, 170, 170);
+    assert_eq!(format!("{:.3}", apca_contrast(&grey, &white)), "63.056");
+    assert_eq!(format!("{:.3}", apca_contrast(&white, &grey)), "-68.541");
+    assert_eq!(format!("{:.3}", apca_contrast(&black, &light_grey)), "58.146");
+    assert_eq!(format!("{:.3}", apca_contrast(&light_grey, &black)), "-56.241");
+    assert_eq!(apca_contrast(&grey, &grey), 0.0);
+    assert_eq!(apca_rating(-68.5), "Content");
+  }
+
+  fn test_colors() -> Colors {
+    Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(144, 72, 93),
+      ColorRGB::new(148, 72, 93),
+      ColorRGB::new(72, 220, 3),
+    ])
+  }
+
+  #[test]
+  fn test_near_duplicates() {
+    let colors = test_colors();
+    let pairs = colors.near_duplicates(DeltaE::Ciede2000, 5.0);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!((pairs[0].0, pairs[0].1), ("color_b", "color_c"));
+    assert_eq!(colors.near_duplicates(DeltaE::Ok, 0.05).len(), 1);
+    // red and green merge for protanopes
+    let colors = colors.with_color(2, ColorRGB::new(200, 60, 60)).with_color(3, ColorRGB::new(104, 88, 56));
+    assert!(colors.near_duplicates(DeltaE::Ciede2000, 5.0).is_empty());
+    assert!(!colors.simulate_cvd(Cvd::Protanopia).near_duplicates(DeltaE::Ciede2000, 5.0).is_empty());
+  }
+
+  #[test]
+  fn test_slots() {
+    let mut colors = test_colors();
+    assert_eq!(colors.named_slots().iter().map(|(name, _)| *name).collect::<Vec<_>>(), DEFAULT_SLOT_NAMES);
+    assert_eq!(colors.accent(), &ColorRGB::new(72, 220, 3));
+    colors.insert(2, ColorRGB::new(1, 2, 3)).unwrap();
+    assert_eq!((colors.len(), colors.name(2), colors.color(2)), (6, "color_d", &ColorRGB::new(1, 2, 3)));
+    colors.move_slot(2, 5).unwrap();
+    assert_eq!(colors.name(5), "color_d");
+    assert!(colors.move_slot(0, 3).is_err());
+    assert!(colors.move_slot(colors.len(), 1).is_err());
+    assert!(colors.remove(0).is_err());
+    assert_eq!(colors.remove(5).unwrap().name, "color_d");
+    colors.swap_colors(0, 1);
+    assert_eq!((colors.name(0), colors.background()), ("background", &ColorRGB::new(255, 255, 255)));
+    let mut two = Colors::from_colors(vec![ColorRGB::new(0, 0, 0), ColorRGB::new(255, 255, 255)]);
+    assert!(two.remove(1).is_err());
+    assert_eq!(two.accent(), &ColorRGB::new(255, 255, 255));
+    // eight slots continue the default names
+    let eight = Colors::from_colors(vec![ColorRGB::default(); 8]);
+    assert_eq!(eight.name(7), "color_f");
+    // recoloring keeps the names, adds further colors and keeps the slots it has no color for
+    let recolored = two.recolored(&eight);
+    assert_eq!((recolored.len(), recolored.name(1), recolored.name(2)), (8, "color_a", "color_b"));
+    let mut recolored = eight.recolored(&two);
+    assert_eq!((recolored.len(), recolored.color(1), recolored.color(2)), (8, &ColorRGB::new(255, 255, 255), &ColorRGB::default()));
+    let dropped = recolored.truncate(2);
+    assert_eq!((recolored.len(), dropped.len(), dropped[0].name.as_str()), (2, 6, "color_b"));
+    assert!(recolored.truncate(0).is_empty());
+  }
+
+  #[test]
+  fn test_colors_serde() {
+    let json = r##"{"fg": "#FFFFFF", "background": "#202020", "red": "#FF0000"}"##;
+    let colors: Colors = serde_json::from_str(json).unwrap();
+    assert_eq!(colors.named_slots().iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["background", "fg", "red"]);
+    assert_eq!(serde_json::to_string(&colors).unwrap(), r##"{"background":"#202020","fg":"#FFFFFF","red":"#FF0000"}"##);
+    assert!(serde_json::from_str::<Colors>(r##"{"background": "#202020"}"##).is_err());
+    assert!(serde_json::from_str::<Colors>(r##"{"a": "#202020", "a": "#FFFFFF"}"##).is_err());
+  }
+
+  #[test]
+  fn test_simulate_cvd() {
+    let red = ColorRGB::new(255, 0, 0);
+    assert_eq!(red.simulate_cvd(Cvd::Protanopia), ColorRGB::new(109, 95, 0));
+    assert_eq!(red.simulate_cvd(Cvd::Deuteranopia), ColorRGB::new(163, 144, 0));
+    assert_eq!(red.simulate_cvd(Cvd::Achromatopsia), ColorRGB::new(127, 127, 127));
+    let grey = ColorRGB::new(119, 119, 119);
+    for cvd in Cvd::ALL {
+      assert_eq!(grey.simulate_cvd(cvd), grey, "{:?}", cvd);
+    }
+  }
+
+  #[test]
+  fn test_helpers_keep_alpha() {
+    let color = ColorRGB::from_rgba(144, 72, 93, 128);
+    let white = ColorRGB::new(255, 255, 255);
+    assert_eq!(color.shift_hue(40.).a, 128);
+    assert_eq!(color.shift_value(-0.2).a, 128);
+    assert_eq!(color.with_lightness(0.8).a, 128);
+    assert_eq!(color.simulate_cvd(Cvd::Deuteranopia).a, 128);
+    assert_eq!(color.shift_oklch_lightness(0.1).a, 128);
+    assert_eq!(color.with_oklch_hue(200.).a, 128);
+    assert_eq!(color.with_contrast_against(&white, 7.0).unwrap().a, 128);
+    let back = color.shift_value(-0.2).shift_value(0.2);
+    assert_eq!(back.a, 128);
+  }
+
+  #[test]
+  fn test_passes_text() {
+    assert!(ContrastMetric::Wcag.passes_text(4.5));
+    assert!(!ContrastMetric::Wcag.passes_text(4.48));
+    assert!(ContrastMetric::Apca.passes_text(-68.5));
+    assert!(!ContrastMetric::Apca.passes_text(58.1));
+  }
+
+  #[test]
+  fn test_contrast_metric_describe() {
+    let white = ColorRGB::new(255, 255, 255);
+    let grey = ColorRGB::new(136, 136, 136);
+    assert_eq!(ContrastMetric::Wcag.describe(&grey, &white), " 3.54:1 AA Large");
+    assert_eq!(ContrastMetric::Apca.describe(&grey, &white), "Lc  63.1 Content");
+  }
 
 
 }
@@ -0,0 +1,188 @@
+//! Quantization to the xterm 256 color palette and the 16 base ANSI colors.
+//!
+//! Indices 0 - 15 are the base colors (shown with the xterm defaults, terminals theme them),
+//! 16 - 231 the 6x6x6 color cube and 232 - 255 the grayscale ramp.
+//! The nearest color is the one with the smallest OKLab distance.
+
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use super::ColorRGB;
+
+static XTERM_OKLAB: OnceLock<Vec<(f64, f64, f64)>> = OnceLock::new();
+
+/// xterm's default values of the 16 base colors.
+pub const ANSI16: [(u8, u8, u8); 16] = [
+  (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+  (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+/// Channel levels of the 6x6x6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Number of colors a terminal can show.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, ValueEnum)]
+pub enum ColorDepth {
+  #[default]
+  #[value(name = "truecolor")]
+  #[serde(rename = "truecolor")]
+  TrueColor,
+  #[value(name = "256")]
+  #[serde(rename = "256")]
+  Ansi256,
+  #[value(name = "16")]
+  #[serde(rename = "16")]
+  Ansi16,
+}
+
+impl ColorDepth {
+  pub const ALL: [ColorDepth; 3] = [ColorDepth::TrueColor, ColorDepth::Ansi256, ColorDepth::Ansi16];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      ColorDepth::TrueColor => "truecolor",
+      ColorDepth::Ansi256 => "256 colors",
+      ColorDepth::Ansi16 => "16 colors",
+    }
+  }
+
+  /// Number of colors, 2^24 for truecolor.
+  pub fn colors(&self) -> u32 {
+    match self {
+      ColorDepth::TrueColor => 1 << 24,
+      ColorDepth::Ansi256 => 256,
+      ColorDepth::Ansi16 => 16,
+    }
+  }
+
+  /// The depth with fewer colors.
+  pub fn lower(self, other: ColorDepth) -> ColorDepth {
+    if other.colors() < self.colors() {other} else {self}
+  }
+
+  /// The color a terminal of this depth shows for `color`. RGB colors become palette indices,
+  /// with 16 colors the named base colors so the basic SGR codes (30 - 37, 90 - 97) are written.
+  pub fn map(&self, color: Color) -> Color {
+    match (self, color) {
+      (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => {Color::Indexed(nearest_ansi256(&ColorRGB::new(r, g, b)))},
+      (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => {ansi16_color(nearest_ansi16(&ColorRGB::new(r, g, b)))},
+      (ColorDepth::Ansi16, Color::Indexed(i)) if i > 15 => {ansi16_color(nearest_ansi16(&xterm_color(i)))},
+      (ColorDepth::Ansi16, Color::Indexed(i)) => {ansi16_color(i)},
+      _ => {color},
+    }
+  }
+}
+
+/// The named color of one of the 16 base colors, indices above 15 stay indexed.
+pub fn ansi16_color(index: u8) -> Color {
+  match index {
+    0 => {Color::Black},
+    1 => {Color::Red},
+    2 => {Color::Green},
+    3 => {Color::Yellow},
+    4 => {Color::Blue},
+    5 => {Color::Magenta},
+    6 => {Color::Cyan},
+    7 => {Color::Gray},
+    8 => {Color::DarkGray},
+    9 => {Color::LightRed},
+    10 => {Color::LightGreen},
+    11 => {Color::LightYellow},
+    12 => {Color::LightBlue},
+    13 => {Color::LightMagenta},
+    14 => {Color::LightCyan},
+    15 => {Color::White},
+    _ => {Color::Indexed(index)},
+  }
+}
+
+/// RGB value of an xterm palette index.
+pub fn xterm_color(index: u8) -> ColorRGB {
+  let (r, g, b) = match index {
+    0..=15 => {ANSI16[index as usize]},
+    16..=231 => {
+      let i = (index - 16) as usize;
+      (CUBE_LEVELS[i / 36], CUBE_LEVELS[i / 6 % 6], CUBE_LEVELS[i % 6])
+    },
+    _ => {
+      let level = 8 + 10 * (index - 232);
+      (level, level, level)
+    },
+  };
+  ColorRGB::new(r, g, b)
+}
+
+/// Nearest index in the color cube and grayscale ramp, the themable base colors are left out.
+pub fn nearest_ansi256(color: &ColorRGB) -> u8 {
+  nearest_in(color, 16..=255)
+}
+
+/// Nearest of the 16 base colors.
+pub fn nearest_ansi16(color: &ColorRGB) -> u8 {
+  nearest_in(color, 0..=15)
+}
+
+fn nearest_in(color: &ColorRGB, indices: std::ops::RangeInclusive<u8>) -> u8 {
+  let palette = XTERM_OKLAB.get_or_init(|| (0..=255).map(|i| xterm_color(i).rgb_to_oklab()).collect());
+  let (l, a, b) = color.rgb_to_oklab();
+  let distance = |(l2, a2, b2): (f64, f64, f64)| (l - l2).powi(2) + (a - a2).powi(2) + (b - b2).powi(2);
+  indices
+    .min_by(|&i, &j| distance(palette[i as usize]).total_cmp(&distance(palette[j as usize])))
+    .expect("the index range is not empty")
+}
+
+impl ColorRGB {
+  /// The palette color a terminal of `depth` shows instead, its index is [`nearest_ansi256`] or [`nearest_ansi16`].
+  pub fn quantize(&self, depth: ColorDepth) -> ColorRGB {
+    match depth {
+      ColorDepth::TrueColor => {self.clone()},
+      ColorDepth::Ansi256 => {xterm_color(nearest_ansi256(self))},
+      ColorDepth::Ansi16 => {xterm_color(nearest_ansi16(self))},
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_xterm_color() {
+    assert_eq!(xterm_color(9), ColorRGB::new(255, 0, 0));
+    assert_eq!(xterm_color(16).to_hex(), "#000000");
+    assert_eq!(xterm_color(196).to_hex(), "#FF0000");
+    assert_eq!(xterm_color(110).to_hex(), "#87AFD7");
+    assert_eq!(xterm_color(232).to_hex(), "#080808");
+    assert_eq!(xterm_color(255).to_hex(), "#EEEEEE");
+  }
+
+  #[test]
+  fn test_nearest() {
+    assert_eq!(nearest_ansi256(&ColorRGB::new(255, 0, 0)), 196);
+    assert_eq!(nearest_ansi256(&ColorRGB::new(128, 128, 128)), 244);
+    assert_eq!(nearest_ansi256(&ColorRGB::new(32, 32, 32)), 234);
+    assert_eq!(nearest_ansi256(&ColorRGB::new(135, 175, 215)), 110);
+    assert_eq!(nearest_ansi16(&ColorRGB::new(255, 10, 10)), 9);
+    assert_eq!(nearest_ansi16(&ColorRGB::new(190, 20, 20)), 1);
+    assert_eq!(nearest_ansi16(&ColorRGB::new(20, 20, 20)), 0);
+  }
+
+  #[test]
+  fn test_map() {
+    assert_eq!(ColorDepth::TrueColor.map(Color::Rgb(255, 0, 0)), Color::Rgb(255, 0, 0));
+    assert_eq!(ColorDepth::Ansi256.map(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+    assert_eq!(ColorDepth::Ansi16.map(Color::Rgb(255, 0, 0)), Color::LightRed);
+    assert_eq!(ColorDepth::Ansi16.map(Color::Rgb(20, 20, 20)), Color::Black);
+    assert_eq!(ColorDepth::Ansi16.map(Color::Indexed(196)), Color::LightRed);
+    assert_eq!(ColorDepth::Ansi16.map(Color::Indexed(4)), Color::Blue);
+    assert_eq!(ColorDepth::Ansi16.map(Color::LightRed), Color::LightRed);
+    assert_eq!(ColorDepth::Ansi256.map(Color::Indexed(4)), Color::Indexed(4));
+    assert_eq!(ColorDepth::Ansi256.map(Color::Reset), Color::Reset);
+    assert_eq!(ColorRGB::new(250, 5, 5).quantize(ColorDepth::Ansi256), xterm_color(196));
+  }
+}
@@ -0,0 +1,288 @@
+//! Parser for the CSS color syntax as copied from browser devtools.
+//!
+//! Accepts hex codes (`#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`), `transparent` and the functions
+//! `rgb()`, `rgba()`, `hsl()`, `hsla()`, `hwb()`, `lab()`, `lch()`, `oklab()`, `oklch()` and `color()`
+//! with the `srgb` and `srgb-linear` spaces, in both the comma and the space separated form,
+//! and the names of the [`names`] lists.
+//! Errors name the function and the component that failed.
+//!
+//! Ref: https://www.w3.org/TR/css-color-4/
+
+use super::{names, rgb_from_hue_chroma, ColorRGB};
+
+const FUNCTIONS: [&str; 10] = ["rgb", "rgba", "hsl", "hsla", "hwb", "lab", "lch", "oklab", "oklch", "color"];
+
+/// A component as written, before it is scaled to the range of its channel.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Value {
+  Number(f64),
+  Percent(f64),
+}
+
+impl Value {
+  /// Resolves percentages relative to `full`, the value of 100%.
+  fn resolve(self, full: f64) -> f64 {
+    match self {
+      Value::Number(x) => {x},
+      Value::Percent(p) => {p / 100.0 * full},
+    }
+  }
+}
+
+/// Parses a CSS color, case-insensitive.
+pub fn parse_css_color(input: &str) -> Result<ColorRGB, String> {
+  let s = input.trim().to_lowercase();
+  if s == "transparent" {
+    return Ok(ColorRGB::from_rgba(0, 0, 0, 0));
+  }
+  if let Some(digits) = s.strip_prefix('#') {
+    return parse_hex(input.trim(), digits);
+  }
+  let Some((name, rest)) = s.split_once('(') else {
+    return names::lookup(&s)
+      .map(|named| named.color.clone())
+      .ok_or_else(|| format!("unknown color `{}`, expected a hex code, a color name or a CSS color function", input.trim()));
+  };
+  let name = name.trim();
+  if !FUNCTIONS.contains(&name) {
+    return Err(format!("unknown color function `{}()`, expected one of: {}", name, FUNCTIONS.join(", ")));
+  }
+  let Some(args) = rest.strip_suffix(')') else {
+    return Err(format!("missing `)` in `{}`", input.trim()));
+  };
+
+  match name {
+    "rgb" | "rgba" => {parse_rgb(name, args)},
+    "hsl" | "hsla" => {parse_hsl(name, args)},
+    "hwb" => {parse_hwb(args)},
+    "lab" => {
+      let (c, alpha) = split_components(name, args, ["lightness", "a", "b"])?;
+      let l = number_or_percent(name, "lightness", c[0])?.resolve(100.0);
+      let a = number_or_percent(name, "a", c[1])?.resolve(125.0);
+      let b = number_or_percent(name, "b", c[2])?.resolve(125.0);
+      with_alpha(ColorRGB::from_lab_d50((l.clamp(0.0, 100.0), a, b)), name, alpha)
+    },
+    "lch" => {
+      let (c, alpha) = split_components(name, args, ["lightness", "chroma", "hue"])?;
+      let l = number_or_percent(name, "lightness", c[0])?.resolve(100.0);
+      let chroma = number_or_percent(name, "chroma", c[1])?.resolve(150.0);
+      let h = angle(name, "hue", c[2])?;
+      with_alpha(ColorRGB::from_lch_d50((l.clamp(0.0, 100.0), chroma.max(0.0), h)), name, alpha)
+    },
+    "oklab" => {
+      let (c, alpha) = split_components(name, args, ["lightness", "a", "b"])?;
+      let l = number_or_percent(name, "lightness", c[0])?.resolve(1.0);
+      let a = number_or_percent(name, "a", c[1])?.resolve(0.4);
+      let b = number_or_percent(name, "b", c[2])?.resolve(0.4);
+      with_alpha(ColorRGB::from_oklab((l.clamp(0.0, 1.0), a, b)), name, alpha)
+    },
+    "oklch" => {
+      let (c, alpha) = split_components(name, args, ["lightness", "chroma", "hue"])?;
+      let l = number_or_percent(name, "lightness", c[0])?.resolve(1.0);
+      let chroma = number_or_percent(name, "chroma", c[1])?.resolve(0.4);
+      let h = angle(name, "hue", c[2])?;
+      with_alpha(ColorRGB::from_oklch((l, chroma, h)), name, alpha)
+    },
+    _ => {parse_color_function(args)},
+  }
+}
+
+fn parse_hex(input: &str, digits: &str) -> Result<ColorRGB, String> {
+  if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+    return Err(format!("invalid hex digit `{}` in `{}`", c, input));
+  }
+  if ![3, 4, 6, 8].contains(&digits.len()) {
+    return Err(format!("`{}` has {} hex digits, expected 3, 4, 6 or 8", input, digits.len()));
+  }
+  ColorRGB::from_hex(&format!("#{}", digits)).map_err(|_| format!("invalid hex color `{}`", input))
+}
+
+fn parse_rgb(name: &str, args: &str) -> Result<ColorRGB, String> {
+  let (c, alpha) = split_components(name, args, ["red", "green", "blue"])?;
+  let mut rgb = [0u8; 3];
+  for (i, channel) in ["red", "green", "blue"].into_iter().enumerate() {
+    rgb[i] = to_channel(number_or_percent(name, channel, c[i])?.resolve(255.0) / 255.0);
+  }
+  with_alpha(ColorRGB::new(rgb[0], rgb[1], rgb[2]), name, alpha)
+}
+
+fn parse_hsl(name: &str, args: &str) -> Result<ColorRGB, String> {
+  let (c, alpha) = split_components(name, args, ["hue", "saturation", "lightness"])?;
+  let h = angle(name, "hue", c[0])?;
+  let s = number_or_percent(name, "saturation", c[1])?.resolve(100.0) / 100.0;
+  let l = number_or_percent(name, "lightness", c[2])?.resolve(100.0) / 100.0;
+  with_alpha(ColorRGB::from_hsl((h.rem_euclid(360.0), s.clamp(0.0, 1.0), l.clamp(0.0, 1.0))), name, alpha)
+}
+
+fn parse_hwb(args: &str) -> Result<ColorRGB, String> {
+  let (c, alpha) = split_components("hwb", args, ["hue", "whiteness", "blackness"])?;
+  let h = angle("hwb", "hue", c[0])?;
+  let w = (number_or_percent("hwb", "whiteness", c[1])?.resolve(100.0) / 100.0).clamp(0.0, 1.0);
+  let b = (number_or_percent("hwb", "blackness", c[2])?.resolve(100.0) / 100.0).clamp(0.0, 1.0);
+  // whiteness and blackness adding up to more than 100% give a grey
+  let color = if w + b >= 1.0 {
+    let grey = to_channel(w / (w + b));
+    ColorRGB::new(grey, grey, grey)
+  } else {
+    // the pure hue scaled down by 1 - w - b and lifted by the whiteness, rounded like the other CSS functions
+    let (r, g, bl) = rgb_from_hue_chroma(h.rem_euclid(360.0), 1.0);
+    let channel = |x: f64| to_channel(x * (1.0 - w - b) + w);
+    ColorRGB::new(channel(r), channel(g), channel(bl))
+  };
+  with_alpha(color, "hwb", alpha)
+}
+
+/// `color(srgb r g b)` and `color(srgb-linear r g b)` with channels between 0. and 1.
+fn parse_color_function(args: &str) -> Result<ColorRGB, String> {
+  let args = args.trim_start();
+  let (space, channels) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+  if !matches!(space, "srgb" | "srgb-linear") {
+    return Err(format!("color(): unsupported color space `{}`, expected srgb or srgb-linear", space));
+  }
+  let (c, alpha) = split_components("color", channels, ["red", "green", "blue"])?;
+  let mut rgb = [0.0; 3];
+  for (i, channel) in ["red", "green", "blue"].into_iter().enumerate() {
+    rgb[i] = number_or_percent("color", channel, c[i])?.resolve(1.0);
+  }
+  let color = if space == "srgb" {
+    ColorRGB::new(to_channel(rgb[0]), to_channel(rgb[1]), to_channel(rgb[2]))
+  } else {
+    ColorRGB::from_linear((rgb[0], rgb[1], rgb[2]))
+  };
+  with_alpha(color, "color", alpha)
+}
+
+/// Splits the arguments into three components and an optional alpha,
+/// either comma separated (`1, 2, 3, 0.5`) or space separated (`1 2 3 / 0.5`).
+fn split_components<'a>(name: &str, args: &'a str, components: [&str; 3]) -> Result<([&'a str; 3], Option<&'a str>), String> {
+  let (values, alpha): (Vec<&str>, Option<&str>) = if args.contains(',') {
+    let mut values: Vec<&str> = args.split(',').map(str::trim).collect();
+    let alpha = if values.len() == 4 { values.pop() } else { None };
+    (values, alpha)
+  } else {
+    let (values, alpha) = match args.split_once('/') {
+      Some((values, alpha)) => {(values, Some(alpha.trim()))},
+      None => {(args, None)},
+    };
+    (values.split_whitespace().collect(), alpha)
+  };
+  if values.len() != 3 {
+    return Err(format!("{}(): expected {}, {} and {} (and an optional alpha), got {} components", name, components[0], components[1], components[2], values.len()));
+  }
+  if let Some(empty) = values.iter().position(|v| v.is_empty()) {
+    return Err(format!("{}(): {} is missing", name, components[empty]));
+  }
+  Ok(([values[0], values[1], values[2]], alpha))
+}
+
+fn number_or_percent(name: &str, component: &str, token: &str) -> Result<Value, String> {
+  if token == "none" {
+    return Ok(Value::Number(0.0));
+  }
+  let parsed = match token.strip_suffix('%') {
+    Some(percent) => {percent.parse::<f64>().map(Value::Percent)},
+    None => {token.parse::<f64>().map(Value::Number)},
+  };
+  parsed
+    .ok()
+    .filter(|v| v.resolve(1.0).is_finite())
+    .ok_or_else(|| format!("{}(): {} `{}` is not a number or percentage", name, component, token))
+}
+
+/// Hue in degrees, from a plain number or one with a `deg`, `grad`, `rad` or `turn` unit.
+fn angle(name: &str, component: &str, token: &str) -> Result<f64, String> {
+  if token == "none" {
+    return Ok(0.0);
+  }
+  let units: [(&str, f64); 4] = [("deg", 1.0), ("grad", 0.9), ("rad", 180.0 / std::f64::consts::PI), ("turn", 360.0)];
+  let (number, scale) = units
+    .into_iter()
+    .find_map(|(unit, scale)| token.strip_suffix(unit).map(|number| (number, scale)))
+    .unwrap_or((token, 1.0));
+  number
+    .parse::<f64>()
+    .ok()
+    .filter(|x| x.is_finite())
+    .map(|x| x * scale)
+    .ok_or_else(|| format!("{}(): {} `{}` is not an angle", name, component, token))
+}
+
+fn with_alpha(color: ColorRGB, name: &str, alpha: Option<&str>) -> Result<ColorRGB, String> {
+  match alpha {
+    None => {Ok(color)},
+    Some(token) => {
+      let a = number_or_percent(name, "alpha", token)?.resolve(1.0);
+      Ok(color.with_alpha(to_channel(a)))
+    },
+  }
+}
+
+/// Maps a channel between 0. and 1. to 0 - 255, clamping out of range values as CSS does.
+fn to_channel(x: f64) -> u8 {
+  (x.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_parse_hex() {
+    assert_eq!(parse_css_color("#F80"), Ok(ColorRGB::new(255, 136, 0)));
+    assert_eq!(parse_css_color("#ff880080"), Ok(ColorRGB::from_rgba(255, 136, 0, 128)));
+    assert_eq!(parse_css_color("transparent"), Ok(ColorRGB::from_rgba(0, 0, 0, 0)));
+    assert_eq!(parse_css_color("RebeccaPurple"), Ok(ColorRGB::new(102, 51, 153)));
+    assert_eq!(parse_css_color("xkcd:puke green"), Ok(ColorRGB::new(154, 174, 7)));
+    assert_eq!(parse_css_color("#12345"), Err("`#12345` has 5 hex digits, expected 3, 4, 6 or 8".to_string()));
+    assert_eq!(parse_css_color("#12345g"), Err("invalid hex digit `g` in `#12345g`".to_string()));
+  }
+
+  #[test]
+  fn test_parse_rgb() {
+    assert_eq!(parse_css_color("rgb(0, 238, 236)"), Ok(ColorRGB::new(0, 238, 236)));
+    assert_eq!(parse_css_color("rgb(255 0 0 / 50%)"), Ok(ColorRGB::from_rgba(255, 0, 0, 128)));
+    assert_eq!(parse_css_color("RGBA(100%, 50%, 0%, 0.25)"), Ok(ColorRGB::from_rgba(255, 128, 0, 64)));
+    assert_eq!(parse_css_color("rgb(300 -5 12.4)"), Ok(ColorRGB::new(255, 0, 12)));
+  }
+
+  #[test]
+  fn test_parse_hsl_hwb() {
+    assert_eq!(parse_css_color("hsl(120deg 100% 25%)"), Ok(ColorRGB::new(0, 128, 0)));
+    assert_eq!(parse_css_color("hsla(0.5turn, 100%, 50%, 1)"), Ok(ColorRGB::new(0, 255, 255)));
+    assert_eq!(parse_css_color("hsl(-120 100% 50%)"), Ok(ColorRGB::new(0, 0, 255)));
+    assert_eq!(parse_css_color("hwb(120 20% 20%)"), Ok(ColorRGB::new(51, 204, 51)));
+    assert_eq!(parse_css_color("hwb(0 60% 60%)"), Ok(ColorRGB::new(128, 128, 128)));
+  }
+
+  #[test]
+  fn test_parse_lab_lch_oklch() {
+    // reference values from the CSS Color 4 conversion code
+    assert_eq!(parse_css_color("lab(50% 0 0)"), Ok(ColorRGB::new(119, 119, 119)));
+    assert_eq!(parse_css_color("lab(54.29% 80.8 69.89)"), Ok(ColorRGB::new(255, 0, 0)));
+    assert_eq!(parse_css_color("lch(54.29% 106.84 40.86)"), Ok(ColorRGB::new(255, 0, 0)));
+    assert_eq!(parse_css_color("oklch(62.8% 0.2577 29.23)"), Ok(ColorRGB::new(255, 0, 0)));
+    assert_eq!(parse_css_color("oklab(0.628 0.2249 0.1258 / 0.5)"), Ok(ColorRGB::from_rgba(255, 0, 0, 128)));
+  }
+
+  #[test]
+  fn test_parse_color_function() {
+    assert_eq!(parse_css_color("color(srgb 1 0.5 0)"), Ok(ColorRGB::new(255, 128, 0)));
+    assert_eq!(parse_css_color("color(srgb 100% 50% 0% / 0.5)"), Ok(ColorRGB::from_rgba(255, 128, 0, 128)));
+    assert_eq!(parse_css_color("color(srgb-linear 0.2158 0.2158 0.2158)"), Ok(ColorRGB::new(128, 128, 128)));
+  }
+
+  #[test]
+  fn test_errors() {
+    assert_eq!(parse_css_color("rgb(255 0)"), Err("rgb(): expected red, green and blue (and an optional alpha), got 2 components".to_string()));
+    assert_eq!(parse_css_color("rgb(255 x 0)"), Err("rgb(): green `x` is not a number or percentage".to_string()));
+    assert_eq!(parse_css_color("rgb(255, , 0)"), Err("rgb(): green is missing".to_string()));
+    assert_eq!(parse_css_color("rgb(1 2 3 / x)"), Err("rgb(): alpha `x` is not a number or percentage".to_string()));
+    assert_eq!(parse_css_color("hsl(12px 50% 50%)"), Err("hsl(): hue `12px` is not an angle".to_string()));
+    assert_eq!(parse_css_color("rgb(1 2 3"), Err("missing `)` in `rgb(1 2 3`".to_string()));
+    assert!(parse_css_color("foo(1 2 3)").unwrap_err().starts_with("unknown color function `foo()`"));
+    assert_eq!(parse_css_color("grean"), Err("unknown color `grean`, expected a hex code, a color name or a CSS color function".to_string()));
+    assert_eq!(parse_css_color("color(display-p3 1 0 0)"), Err("color(): unsupported color space `display-p3`, expected srgb or srgb-linear".to_string()));
+  }
+}
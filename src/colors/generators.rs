@@ -1,46 +1,462 @@
+/// Declares a unit struct implementing [`PaletteGenerator`] with the function that generates its palettes.
+macro_rules! palette_generator {
+    ($generator:ident, $name:literal, $description:literal, $params:expr, $generate:ident) => {
+        pub struct $generator;
+
+        impl $crate::colors::generators::PaletteGenerator for $generator {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn description(&self) -> &'static str {
+                $description
+            }
+
+            fn params(&self) -> &'static [$crate::colors::generators::Param] {
+                $params
+            }
+
+            fn generate(&self, color: &$crate::colors::ColorRGB, space: $crate::colors::perceptual::ColorSpace, params: &$crate::colors::generators::HarmonyParams) -> $crate::colors::Colors {
+                $generate(color.clone(), space, params)
+            }
+        }
+    };
+}
+
 pub mod monochromatic;
 pub mod analogous;
 pub mod complementary;
 pub mod triadic;
 pub mod tetradic;
 pub mod split_complementary;
+pub mod random;
 
 
 use super::*;
+use serde::{Deserialize, Serialize};
+use perceptual::{ColorSpace, OKLCH_MAX_CHROMA};
+
+use std::fmt;
+
+use serde::{de, Deserializer, Serializer};
 
 use generators::{
-	monochromatic::generate_monochromatic, 
-	analogous::generate_analogous, 
-	complementary::generate_complementary, 
-	triadic::generate_triadic, 
-	tetradic::generate_tetradic,
-	split_complementary::generate_split_complementary,
+	monochromatic::Monochromatic, 
+	analogous::Analogous, 
+	complementary::Complementary, 
+	triadic::Triadic, 
+	tetradic::Tetradic,
+	split_complementary::SplitComplementary,
+	random::Random,
 };
 
-#[derive(Default, Copy, Clone, PartialEq, Eq)]
-pub enum Harmony {
+/// A palette generation algorithm. New ones are added to the [`registry`], the Palette popup
+/// and the command line list whatever is registered.
+pub trait PaletteGenerator: Sync {
+    /// Name as used in palette files and on the command line.
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// The parameters the generator reads, the Palette popup offers these.
+    fn params(&self) -> &'static [Param];
+
+    /// A palette of `params.count` colors from `color`, background first.
+    fn generate(&self, color: &ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors;
+}
+
+static REGISTRY: [&dyn PaletteGenerator; 7] = [&Monochromatic, &Analogous, &Complementary, &SplitComplementary, &Triadic, &Tetradic, &Random];
+
+/// Every generator, in the order the Palette popup shows them.
+pub fn registry() -> &'static [&'static dyn PaletteGenerator] {
+    &REGISTRY
+}
+
+/// Parameters of the generators that rotate hues by the spread.
+const SPREAD_PARAMS: &[Param] = &[Param::HueSpread, Param::LightnessRange, Param::Saturation, Param::Count];
+/// Parameters of the generators with fixed hues.
+const SHADE_PARAMS: &[Param] = &[Param::LightnessRange, Param::Saturation, Param::Count];
+/// Parameters of the generators that pick hues from a seed.
+const SEED_PARAMS: &[Param] = &[Param::Seed, Param::LightnessRange, Param::Saturation, Param::Count];
+
+/// A registered generator, stored in palette files and parsed from the command line by name.
+#[derive(Copy, Clone)]
+pub struct Harmony(&'static dyn PaletteGenerator);
+
+impl Harmony {
+    pub const MONOCHROMATIC: Harmony = Harmony(&Monochromatic);
+    pub const ANALOGOUS: Harmony = Harmony(&Analogous);
+    pub const COMPLEMENTARY: Harmony = Harmony(&Complementary);
+    pub const SPLIT_COMPLEMENTARY: Harmony = Harmony(&SplitComplementary);
+    pub const TRIADIC: Harmony = Harmony(&Triadic);
+    pub const TETRADIC: Harmony = Harmony(&Tetradic);
+    pub const RANDOM: Harmony = Harmony(&Random);
+
+    /// Every registered generator.
+    pub fn all() -> impl Iterator<Item = Harmony> {
+        registry().iter().map(|generator| Harmony(*generator))
+    }
+
+    /// Name as used in palette files and on the command line.
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    pub fn generator(&self) -> &'static dyn PaletteGenerator {
+        self.0
+    }
+}
+
+impl Default for Harmony {
+    fn default() -> Self {
+        Harmony(registry()[0])
+    }
+}
+
+impl PartialEq for Harmony {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for Harmony {}
+
+impl fmt::Debug for Harmony {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Harmony").field(&self.name()).finish()
+    }
+}
+
+impl fmt::Display for Harmony {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Harmony {
+    type Err = String;
+
+    /// Parses a generator name, ignoring case, `-` and `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.to_lowercase().replace(['-', '_', ' '], "");
+        Harmony::all()
+            .find(|h| h.name().replace('_', "") == normalized)
+            .ok_or_else(|| format!("unknown harmony `{}`, expected one of: {}", s, Harmony::all().map(|h| h.name()).collect::<Vec<_>>().join(", ")))
+    }
+}
+
+impl Serialize for Harmony {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Harmony {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// How saturated the generated colors are.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Saturation {
+    /// The base color's saturation (chroma in OKLCH)
     #[default]
-    Monochromatic, // just shade/tint
-    Complementary,  // one opposite color plus shades
-    SplitComplementary, // two opposite colors plus shades
-    Triadic,    // even triangle, split 360/3 -> 120 input color + 120 + 120
-    Tetradic,   // get one analogous color and its complement + selfs complement
-    Analogous, // next to each other... what is next.. how many degrees? 15° ?
+    Keep,
+    /// Half the base color's saturation
+    Muted,
+    /// Full saturation, in OKLCH as much chroma as the gamut allows
+    Vivid,
+}
+
+impl Saturation {
+    pub const ALL: [Saturation; 3] = [Saturation::Keep, Saturation::Muted, Saturation::Vivid];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Saturation::Keep => "keep",
+            Saturation::Muted => "muted",
+            Saturation::Vivid => "vivid",
+        }
+    }
+
+    /// The saturation-like component (`s` of HSV, chroma of OKLCH) a generated color gets instead of `s`.
+    pub fn apply(&self, s: f64, space: ColorSpace) -> f64 {
+        match (self, space) {
+            (Saturation::Keep, _) => {s},
+            (Saturation::Muted, _) => {s * 0.5},
+            (Saturation::Vivid, ColorSpace::Hsv) => {1.0},
+            (Saturation::Vivid, ColorSpace::Oklch) => {OKLCH_MAX_CHROMA},
+        }
+    }
+}
+
+/// Parameters of the harmony generators, the defaults give the classic palettes.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HarmonyParams {
+    /// Degrees between analogous hues, split complementary hues are 180 minus this apart
+    pub hue_spread: f64,
+    /// Shades are the base lightness times 1 +- this, monochromatic spans 0.5 +- twice this
+    pub lightness_range: f64,
+    pub saturation: Saturation,
+    /// Number of colors, from 2 to 32
+    pub count: usize,
+    /// Picks the hues of random palettes
+    pub seed: u64,
+}
+
+impl Default for HarmonyParams {
+    fn default() -> Self {
+        HarmonyParams { hue_spread: 30.0, lightness_range: 0.2, saturation: Saturation::Keep, count: 5, seed: 0 }
+    }
+}
+
+/// A generator parameter, a field of [`HarmonyParams`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Param {
+    HueSpread,
+    LightnessRange,
+    Saturation,
+    Count,
+    Seed,
+}
+
+impl Param {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Param::HueSpread => "spread",
+            Param::LightnessRange => "lightness",
+            Param::Saturation => "saturation",
+            Param::Count => "count",
+            Param::Seed => "seed",
+        }
+    }
+
+    /// The value in `params` as shown in the Palette popup.
+    pub fn describe(&self, params: &HarmonyParams) -> String {
+        match self {
+            Param::HueSpread => format!("Spread {}°", params.hue_spread),
+            Param::LightnessRange => format!("Lightness ±{:.0}%", params.lightness_range * 100.0),
+            Param::Saturation => format!("Saturation {}", params.saturation.name()),
+            Param::Count => format!("Colors {}", params.count),
+            Param::Seed => format!("Seed {}", params.seed),
+        }
+    }
+
+    /// Steps the value in `params` up or down, within its range.
+    pub fn step(&self, params: &mut HarmonyParams, up: bool) {
+        let sign = if up {1.0} else {-1.0};
+        match self {
+            Param::HueSpread => {params.hue_spread = (params.hue_spread + sign * 5.0).clamp(0.0, 180.0);},
+            Param::LightnessRange => {params.lightness_range = ((params.lightness_range + sign * 0.05) * 100.0).round().clamp(0.0, 50.0) / 100.0;},
+            Param::Saturation => {
+                let idx = Saturation::ALL.iter().position(|s| *s == params.saturation).unwrap_or(0) as isize + sign as isize;
+                params.saturation = Saturation::ALL[idx.rem_euclid(Saturation::ALL.len() as isize) as usize];
+            },
+            Param::Count => {params.count = if up {(params.count + 1).min(MAX_SLOTS)} else {params.count.saturating_sub(1).max(MIN_SLOTS)};},
+            Param::Seed => {params.seed = if up {params.seed.wrapping_add(1)} else {params.seed.saturating_sub(1)};},
+        }
+    }
 }
 
-pub fn generate_palette_with_harmony(color: ColorRGB, harmony: Harmony) -> Colors {
-    match harmony {
-        Harmony::Monochromatic => {generate_monochromatic(color)},
-        Harmony::Complementary => {generate_complementary(color, 5)},
-        Harmony::SplitComplementary => {generate_split_complementary(color)},
-        Harmony::Triadic => {generate_triadic(color)},
-        Harmony::Tetradic => {generate_tetradic(color)},
-        Harmony::Analogous => {generate_analogous(color)},
+impl HarmonyParams {
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// A color of `hue` and `lightness` with the base's saturation after the saturation policy.
+    fn color(&self, space: ColorSpace, base: (f64, f64, f64), hue: f64, lightness: f64) -> ColorRGB {
+        space.from_polar((hue, self.saturation.apply(base.1, space), lightness))
+    }
+
+    fn lighter(&self, lightness: f64) -> f64 {
+        (lightness * (1.0 + self.lightness_range)).clamp(0.0, 1.0)
+    }
+
+    fn darker(&self, lightness: f64) -> f64 {
+        (lightness * (1.0 - self.lightness_range)).clamp(0.0, 1.0)
     }
 }
 
+/// `generated` applied to the slots of `colors`, keeping the `locked` slots as they are.
+///
+/// Every locked color stands in for the generated color nearest to it (OKLab), the other slots
+/// get the remaining generated colors in order; slots past `generated` keep their colors.
+pub fn keep_locked(colors: &Colors, generated: &Colors, locked: &[usize]) -> Colors {
+    let locked: Vec<usize> = locked.iter().copied().filter(|&idx| idx < colors.len().min(generated.len())).collect();
+    let mut rest: Vec<ColorRGB> = generated.colors().cloned().collect();
+    for &idx in locked.iter() {
+        let color = colors.color(idx);
+        if let Some(nearest) = (0..rest.len()).min_by(|&i, &j| color.delta_e_ok(&rest[i]).total_cmp(&color.delta_e_ok(&rest[j]))) {
+            rest.remove(nearest);
+        }
+    }
+    let mut rest = rest.into_iter();
+    let palette: Vec<ColorRGB> = (0..generated.len())
+        .map(|idx| if locked.contains(&idx) {colors.color(idx).clone()} else {rest.next().expect("one generated color per unlocked slot")})
+        .collect();
+    colors.recolored(&Colors::from_colors(palette))
+}
+
+/// Generates a palette of `params.count` slots by rotating hues and scaling lightness of `color` in the given space.
+pub fn generate_palette_with_harmony(color: ColorRGB, harmony: Harmony, space: ColorSpace, params: &HarmonyParams) -> Colors {
+    let params = params.with_count(params.count.clamp(MIN_SLOTS, MAX_SLOTS));
+    harmony.generator().generate(&color, space, &params)
+}
+
+/// Fits a harmony's colors, background first, to `params.count` slots. Extra slots take the harmony's
+/// foreground colors in turn, lighter in the first round, darker in the second, and so on.
+fn fit_to_size(mut palette: Vec<ColorRGB>, space: ColorSpace, params: &HarmonyParams) -> Colors {
+    let size = params.count;
+    let harmony_len = palette.len() - 1;
+    for extra in 0..size.saturating_sub(palette.len()) {
+        let (h, s, l) = space.to_polar(&palette[1 + extra % harmony_len]);
+        let round = extra / harmony_len + 1;
+        let step = 0.75 * params.lightness_range * round.div_ceil(2) as f64;
+        let factor = if round % 2 == 1 {1.0 + step} else {1.0 - step};
+        palette.push(space.from_polar((h, s, (l * factor).clamp(0.0, 1.0))));
+    }
+    palette.truncate(size.max(MIN_SLOTS));
+    Colors::from_colors(palette)
+}
+
 // shouldnt be here
-pub fn contrast_with_inverted(color: &ColorRGB) -> f32 {
+pub fn contrast_with_inverted(color: &ColorRGB) -> f64 {
     let inv = color.with_flip_rgb();
-    get_contrast(color, &inv)
-}
\ No newline at end of file
+    contrast_ratio(color, &inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use super::*;
+
+    #[test]
+    fn test_sizes() {
+        let color = ColorRGB::from_hex("#00EEEC").unwrap();
+        for harmony in Harmony::all() {
+            for size in [2, 5, 8, 16, 24] {
+                let colors = generate_palette_with_harmony(color.clone(), harmony, ColorSpace::Hsv, &HarmonyParams::default().with_count(size));
+                assert_eq!(colors.len(), size, "{}", harmony);
+            }
+            // a larger palette starts with the five color one
+            let five = generate_palette_with_harmony(color.clone(), harmony, ColorSpace::Oklch, &HarmonyParams::default());
+            let eight = generate_palette_with_harmony(color.clone(), harmony, ColorSpace::Oklch, &HarmonyParams::default().with_count(8));
+            if harmony != Harmony::MONOCHROMATIC {
+                assert_eq!(eight.colors().take(5).collect::<Vec<_>>(), five.colors().collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_params() {
+        let color = ColorRGB::from_hex("#00EEEC").unwrap();
+        let (hue, saturation, value) = color.rgb_to_hsv();
+        let params = HarmonyParams { hue_spread: 45.0, ..Default::default() };
+        // color_c of analogous is one spread away from the base
+        let colors = generate_palette_with_harmony(color.clone(), Harmony::ANALOGOUS, ColorSpace::Hsv, &params);
+        assert!((colors.color(3).rgb_to_hsv().0 - (hue + 45.0)).abs() < 1.0);
+        // without a lightness range the shades are the base color
+        let params = HarmonyParams { lightness_range: 0.0, ..Default::default() };
+        let colors = generate_palette_with_harmony(color.clone(), Harmony::TRIADIC, ColorSpace::Hsv, &params);
+        assert!((colors.background().rgb_to_hsv().2 - value).abs() < 0.01);
+        let params = HarmonyParams { saturation: Saturation::Muted, ..Default::default() };
+        let colors = generate_palette_with_harmony(color.clone(), Harmony::TRIADIC, ColorSpace::Hsv, &params);
+        assert!((colors.color(2).rgb_to_hsv().1 - saturation * 0.5).abs() < 0.01);
+        // the base color itself is kept
+        assert_eq!(colors.color(1), &color);
+        assert_eq!(Saturation::Vivid.apply(0.1, ColorSpace::Oklch), OKLCH_MAX_CHROMA);
+    }
+
+    #[test]
+    fn test_registry() {
+        let names: Vec<&str> = Harmony::all().map(|h| h.name()).collect();
+        assert_eq!(names, ["monochromatic", "analogous", "complementary", "split_complementary", "triadic", "tetradic", "random"]);
+        assert_eq!(Harmony::default().name(), "monochromatic");
+        assert_eq!("Split-Complementary".parse::<Harmony>(), Ok(Harmony::SPLIT_COMPLEMENTARY));
+        assert!("pentadic".parse::<Harmony>().unwrap_err().contains("tetradic"));
+        assert_eq!(serde_json::to_string(&Harmony::TRIADIC).unwrap(), "\"triadic\"");
+        for harmony in Harmony::all() {
+            assert!(harmony.generator().params().contains(&Param::Count), "{}", harmony);
+        }
+    }
+
+    #[test]
+    fn test_param_step() {
+        let mut params = HarmonyParams::default();
+        Param::LightnessRange.step(&mut params, true);
+        assert_eq!(params.lightness_range, 0.25);
+        Param::Saturation.step(&mut params, false);
+        assert_eq!(params.saturation, Saturation::Vivid);
+        params.count = MAX_SLOTS;
+        Param::Count.step(&mut params, true);
+        assert_eq!(params.count, MAX_SLOTS);
+        assert_eq!(Param::HueSpread.describe(&params), "Spread 30°");
+    }
+
+    #[test]
+    fn test_random() {
+        let color = ColorRGB::new(0, 238, 236);
+        let random = Harmony::RANDOM;
+        let params = HarmonyParams::default().with_count(8);
+        let palette = generate_palette_with_harmony(color.clone(), random, ColorSpace::Hsv, &params);
+        assert_eq!(palette.len(), 8);
+        assert_eq!(palette.color(1), &color);
+        assert_eq!(palette, generate_palette_with_harmony(color.clone(), random, ColorSpace::Hsv, &params));
+        let reseeded = HarmonyParams { seed: 1, ..params };
+        assert_ne!(palette, generate_palette_with_harmony(color, random, ColorSpace::Hsv, &reseeded));
+    }
+
+    #[test]
+    fn test_keep_locked() {
+        let colors = Colors::from_colors(vec![
+            ColorRGB::new(50, 170, 10),
+            ColorRGB::new(250, 250, 250),
+            ColorRGB::new(200, 40, 40),
+            ColorRGB::new(40, 40, 200),
+            ColorRGB::new(72, 220, 3),
+        ]);
+        let generated = generate_palette_with_harmony(ColorRGB::new(72, 220, 3), Harmony::TRIADIC, ColorSpace::Hsv, &HarmonyParams::default());
+        assert_eq!(keep_locked(&colors, &generated, &[]), colors.recolored(&generated));
+
+        // the locked background and highlight stand in for the generated dark shade and base color, which are not repeated
+        let kept = keep_locked(&colors, &generated, &[0, 4]);
+        assert_eq!(kept.len(), 5);
+        assert_eq!(kept.name(4), "highlight");
+        assert_eq!(kept.background(), colors.background());
+        assert_eq!(kept.color(4), colors.color(4));
+        let unlocked: Vec<&ColorRGB> = (1..4).map(|idx| kept.color(idx)).collect();
+        assert_eq!(unlocked, [generated.color(2), generated.color(3), generated.color(4)]);
+
+        // slots past fewer generated colors are kept as they are
+        let kept = keep_locked(&colors, &Colors::from_colors(generated.colors().take(3).cloned().collect()), &[1, 4]);
+        assert_eq!(kept.len(), 5);
+        assert_eq!(kept.color(1), colors.color(1));
+        assert_eq!(kept.color(4), colors.color(4));
+    }
+
+    #[test]
+    fn test_wide_lightness_range() {
+        let params = HarmonyParams { lightness_range: 0.5, ..HarmonyParams::default() };
+        assert_eq!(params.lighter(0.9), 1.0);
+        let monochromatic = Harmony::MONOCHROMATIC;
+        for color in [ColorRGB::new(0, 238, 236), ColorRGB::new(128, 64, 64), ColorRGB::new(40, 20, 90)] {
+            for size in [5, 9] {
+                let palette = generate_palette_with_harmony(color.clone(), monochromatic, ColorSpace::Hsv, &params.with_count(size));
+                let hexes: Vec<String> = palette.colors().map(|c| c.to_hex()).collect();
+                for (i, hex) in hexes.iter().enumerate() {
+                    assert!(!hexes[..i].contains(hex), "{} repeats in {:?}", hex, hexes);
+                }
+            }
+        }
+    }
+}
@@ -1,35 +1,33 @@
-use crate::colors::{ColorRGB, Colors};
+use crate::colors::{perceptual::ColorSpace, ColorRGB, Colors};
 
-pub fn generate_analogous(color: ColorRGB) -> Colors {
+use super::{fit_to_size, HarmonyParams, SPREAD_PARAMS};
+
+palette_generator!(Analogous, "analogous", "Neighboring hues a spread apart, with a lighter and a darker shade", SPREAD_PARAMS, generate_analogous);
+
+pub fn generate_analogous(color: ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors {
     // need to find if the passed color is light or dark
-    let base_hsv = color.rgb_to_hsv();
-    let base_hue = base_hsv.0;
-    const HUE_DIFFERENCE: f64 = 30.0;
+    let base = space.to_polar(&color);
+    let base_hue = base.0;
     const NUM_COLORS: usize = 3;
 
     let mut palette = Vec::with_capacity(NUM_COLORS);
 
     for i in 0..NUM_COLORS {
-        let hue = (base_hue + i as f64 * HUE_DIFFERENCE) % 360.0;
-        let analogous_color = ColorRGB::from_hsv((hue, base_hsv.1, base_hsv.2));
+        let hue = (base_hue + i as f64 * params.hue_spread) % 360.0;
+        let analogous_color = params.color(space, base, hue, base.2);
         palette.push(analogous_color);
     }
 
     // Generate lighter and darker shades
-    let lighter_shade = ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 1.2));
-    let darker_shade = ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 0.8));
+    let lighter_shade = params.color(space, base, base_hue, params.lighter(base.2));
+    let darker_shade = params.color(space, base, base_hue, params.darker(base.2));
 
     palette.push(lighter_shade);
     palette.push(darker_shade);
 
 
-    Colors {
-        background: palette[4].clone(),
-        color_a: palette[0].clone(),
-        color_b: palette[3].clone(),
-        color_c: palette[1].clone(),
-        highlight: palette[2].clone(),
-    }
+    let palette = vec![palette[4].clone(), palette[0].clone(), palette[3].clone(), palette[1].clone(), palette[2].clone()];
+    fit_to_size(palette, space, params)
 }
 
 #[cfg(test)]
@@ -44,14 +42,14 @@ mod tests {
         // hsv: 179° 100% 93.3%
 
         let color = ColorRGB::from_hex("#00EEEC").unwrap();
-        let colors = generate_analogous(color);
+        let colors = generate_analogous(color, ColorSpace::Hsv, &HarmonyParams::default());
         println!(
             "0: {}, 1: {}, 2: {}, 3: {}, 4: {}",
-            colors.background.color.to_string(),
-            colors.color_a.color.to_string(),
-            colors.color_b.color.to_string(),
-            colors.color_c.color.to_string(),
-            colors.highlight.color.to_string()
+            colors.color(0).color,
+            colors.color(1).color,
+            colors.color(2).color,
+            colors.color(3).color,
+            colors.color(4).color
         );
         //assert_eq!(hex, "#00EEEC".to_string());
     }
@@ -1,32 +1,31 @@
-use crate::colors::{ColorRGB, Colors};
+use crate::colors::{perceptual::ColorSpace, ColorRGB, Colors};
 
-pub fn generate_complementary(color: ColorRGB, num_colors: usize) -> Colors {
+use super::{fit_to_size, HarmonyParams, SHADE_PARAMS};
+
+palette_generator!(Complementary, "complementary", "The base and its opposite hue, with shades of both", SHADE_PARAMS, generate_complementary);
+
+pub fn generate_complementary(color: ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors {
     // need to find if the passed color is light or dark
     // need to find if the passed color is light or dark
-    let base_hsv = color.rgb_to_hsv();
-    let base_hue = base_hsv.0;
+    let base = space.to_polar(&color);
+    let base_hue = base.0;
 
     // Generate lighter and darker shades for the base color
-    let lighter_shade = ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 1.2));
-    let darker_shade = ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 0.8));
+    let lighter_shade = params.color(space, base, base_hue, params.lighter(base.2));
+    let darker_shade = params.color(space, base, base_hue, params.darker(base.2));
 
     // Generate the complementary color
-    let complementary_color = ColorRGB::from_hsv(((base_hue + 180.0) % 360.0, base_hsv.1, base_hsv.2));
+    let complementary_color = params.color(space, base, (base_hue + 180.0) % 360.0, base.2);
 
-    // Generate an additional shade for the complementary color
-    let additional_shade = if base_hsv.2 < 0.5 {
-        ColorRGB::from_hsv(((base_hue + 180.0) % 360.0, base_hsv.1, base_hsv.2 * 0.6)) // Darker shade
+    // Generate an additional shade for the complementary color, twice as far
+    let additional_shade = if base.2 < 0.5 {
+        params.color(space, base, (base_hue + 180.0) % 360.0, base.2 * (1.0 - 2.0 * params.lightness_range)) // Darker shade
     } else {
-        ColorRGB::from_hsv(((base_hue + 180.0) % 360.0, base_hsv.1, base_hsv.2 * 1.4)) // Lighter shade
+        params.color(space, base, (base_hue + 180.0) % 360.0, base.2 * (1.0 + 2.0 * params.lightness_range)) // Lighter shade
     };
 
-    Colors {
-        background: darker_shade.clone(), // 
-        color_a: color.clone(),
-        color_b: lighter_shade.clone(),
-        color_c: additional_shade.clone(),
-        highlight: complementary_color.clone(),
-    }
+    let palette = vec![darker_shade, color.clone(), lighter_shade, additional_shade, complementary_color];
+    fit_to_size(palette, space, params)
     
 }
 
@@ -42,8 +41,8 @@ mod tests {
     // hsv: 179° 100% 93.3%
 
     let color = ColorRGB::from_hex("#00EEEC").unwrap();
-		let colors = generate_complementary(color, 5);
-		println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.background.color.to_string(), colors.color_a.color.to_string(), colors.color_b.color.to_string(), colors.color_c.color.to_string(), colors.highlight.color.to_string());
+		let colors = generate_complementary(color, ColorSpace::Hsv, &HarmonyParams::default());
+		println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.color(0).color, colors.color(1).color, colors.color(2).color, colors.color(3).color, colors.color(4).color);
     //assert_eq!(hex, "#00EEEC".to_string());
   }  
 }
\ No newline at end of file
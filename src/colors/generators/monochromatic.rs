@@ -1,41 +1,46 @@
 
-use crate::colors::{ColorRGB, Colors};
+use crate::colors::{perceptual::ColorSpace, ColorRGB, Colors};
+
+use super::{HarmonyParams, SHADE_PARAMS};
+
+palette_generator!(Monochromatic, "monochromatic", "Shades of the base color from dark to light", SHADE_PARAMS, generate_monochromatic);
 
 /// Example 
 /// ```
 /// 
 /// let color = ColorRGB::from_hex("#976cdc").unwrap()
-/// let colors = generate_monochromatic(color)
+/// let colors = generate_monochromatic(color, ColorSpace::Hsv, &HarmonyParams::default())
 /// 
-/// println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.background.color.to_string(), colors.color_a.color.to_string(), colors.color_b.color.to_string(), colors.color_c.color.to_string(), colors.highlight.color.to_string());
+/// println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.color(0).color, colors.color(1).color, colors.color(2).color, colors.color(3).color, colors.color(4).color);
 /// 
 /// ```
-pub fn generate_monochromatic(color: ColorRGB) -> Colors {
+pub fn generate_monochromatic(color: ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors {
     // need to find if the passed color is light or dark
     //let lum = get_luminance(&color);
-    const NUM_SHADES: usize = 5;
+    let num_shades = params.count.max(2);
     // bkg color needs 4.5:1 contrast with self - yes!
-    let base_hsv = color.rgb_to_hsv();
-    let mut palette = Vec::with_capacity(NUM_SHADES);
+    let base = space.to_polar(&color);
+    let mut palette = Vec::with_capacity(num_shades);
 
-		let base_v = base_hsv.2;
+		let base_v = base.2;
 
 		let low_lim: f64;
 		let up_lim: f64;
 
-		if base_v < 0.5 {low_lim = base_v} else {low_lim = 0.1};
-		if base_v > 0.5 {up_lim = base_v} else {up_lim = 0.9};  
+		// 0.1 to 0.9 with the default range, black to white from 25% on
+		if base_v < 0.5 {low_lim = base_v} else {low_lim = (0.5 - 2.0 * params.lightness_range).clamp(0.0, 1.0)};
+		if base_v > 0.5 {up_lim = base_v} else {up_lim = (0.5 + 2.0 * params.lightness_range).clamp(0.0, 1.0)};  
 		
 
 
     const MIN_TINT_VALUE: f64 = 0.4;
     const MAX_SHADE_VALUE: f64 = 0.6;
 
-    for i in 0..NUM_SHADES {
-        let value = (i as f64) / ((NUM_SHADES - 1) as f64);
+    for i in 0..num_shades {
+        let value = (i as f64) / ((num_shades - 1) as f64);
         let adjusted_v = low_lim + value * (up_lim - low_lim);
 
-        let shade = ColorRGB::from_hsv((base_hsv.0, base_hsv.1, adjusted_v));
+        let shade = params.color(space, base, base.0, adjusted_v);
         palette.push(shade);
     }
 
@@ -44,12 +49,7 @@ pub fn generate_monochromatic(color: ColorRGB) -> Colors {
     // calculate their contrast
 		// TODO improve contrasts
 
-		Colors { 
-			background: palette[0].clone(), 
-			color_a: palette[1].clone(), 
-			color_b: palette[2].clone(), 
-			color_c: palette[3].clone(), 
-			highlight: palette[4].clone() }
+		Colors::from_colors(palette)
 
 }
 
@@ -65,8 +65,8 @@ mod tests {
     // hsv: 179° 100% 93.3%
 
     let color = ColorRGB::from_hex("#00EEEC").unwrap();
-		let colors = generate_monochromatic(color);
-		println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.background.color.to_string(), colors.color_a.color.to_string(), colors.color_b.color.to_string(), colors.color_c.color.to_string(), colors.highlight.color.to_string());
+		let colors = generate_monochromatic(color, ColorSpace::Hsv, &HarmonyParams::default());
+		println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.color(0).color, colors.color(1).color, colors.color(2).color, colors.color(3).color, colors.color(4).color);
     //assert_eq!(hex, "#00EEEC".to_string());
   }  
 }
\ No newline at end of file
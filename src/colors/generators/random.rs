@@ -0,0 +1,29 @@
+use crate::colors::{perceptual::ColorSpace, ColorRGB, Colors};
+
+use super::{HarmonyParams, SEED_PARAMS};
+
+palette_generator!(Random, "random", "Random hues around the base lightness, a new seed gives new ones", SEED_PARAMS, generate_random);
+
+/// A darker shade of the base as background, the base, then hues picked from `params.seed` and the base color.
+/// The same seed and base give the same palette, so the preview is what gets applied.
+pub fn generate_random(color: ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors {
+    let base = space.to_polar(&color);
+    let mut state = params.seed ^ ((color.r as u64) << 16 | (color.g as u64) << 8 | color.b as u64);
+    // splitmix64, uniform in [0, 1)
+    let mut next = || {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        ((z ^ (z >> 31)) >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    let background = params.color(space, base, base.0, params.darker(params.darker(base.2)));
+    let mut palette = vec![background, color.clone()];
+    while palette.len() < params.count {
+        let hue = next() * 360.0;
+        let lightness = (base.2 * (1.0 + params.lightness_range * (2.0 * next() - 1.0))).clamp(0.0, 1.0);
+        palette.push(params.color(space, base, hue, lightness));
+    }
+    Colors::from_colors(palette)
+}
@@ -1,37 +1,36 @@
-use crate::colors::{ColorRGB, Colors};
+use crate::colors::{perceptual::ColorSpace, ColorRGB, Colors};
 
-pub fn generate_split_complementary(color: ColorRGB) -> Colors {
+use super::{fit_to_size, HarmonyParams, SPREAD_PARAMS};
+
+palette_generator!(SplitComplementary, "split_complementary", "The base and the two hues beside its complement, with shades", SPREAD_PARAMS, generate_split_complementary);
+
+pub fn generate_split_complementary(color: ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors {
     // need to find if the passed color is light or dark
-    let base_hsv = color.rgb_to_hsv();
-    let base_hue = base_hsv.0;
+    let base = space.to_polar(&color);
+    let base_hue = base.0;
 
     let mut palette = Vec::with_capacity(5);
 
     // Add the original color to the palette
     palette.push(color.clone());
 
-    // Generate two additional colors, spaced 150 degrees apart
+    // Generate two additional colors, spaced 180 degrees minus the spread apart
     for i in 0..2 {
-        let hue = (base_hue + 150.0 * ((i + 1) as f64)) % 360.0;
-        let split_complementary_color = ColorRGB::from_hsv((hue, base_hsv.1, base_hsv.2));
+        let hue = (base_hue + (180.0 - params.hue_spread) * ((i + 1) as f64)) % 360.0;
+        let split_complementary_color = params.color(space, base, hue, base.2);
         palette.push(split_complementary_color);
     }
 
     // Generate two shades of the passed color
-    let darker_shade = ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 0.8));
-    let lighter_shade = ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 1.2));
+    let darker_shade = params.color(space, base, base_hue, params.darker(base.2));
+    let lighter_shade = params.color(space, base, base_hue, params.lighter(base.2));
 
     
     palette.push(lighter_shade);
     palette.push(darker_shade);
     
-    Colors {
-        background: palette[4].clone(),
-        color_a: palette[0].clone(),
-        color_b: palette[1].clone(),
-        color_c: palette[2].clone(),
-        highlight: palette[3].clone(),
-    }
+    let palette = vec![palette[4].clone(), palette[0].clone(), palette[1].clone(), palette[2].clone(), palette[3].clone()];
+    fit_to_size(palette, space, params)
 
 
 }
@@ -48,8 +47,8 @@ mod tests {
       // hsv: 179° 100% 93.3%
   
       let color = ColorRGB::from_hex("#00EEEC").unwrap();
-          let colors = generate_split_complementary(color);
-          println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.background.color.to_string(), colors.color_a.color.to_string(), colors.color_b.color.to_string(), colors.color_c.color.to_string(), colors.highlight.color.to_string());
+          let colors = generate_split_complementary(color, ColorSpace::Hsv, &HarmonyParams::default());
+          println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.color(0).color, colors.color(1).color, colors.color(2).color, colors.color(3).color, colors.color(4).color);
       //assert_eq!(hex, "#00EEEC".to_string());
     }  
   }
\ No newline at end of file
@@ -1,10 +1,14 @@
 
-use crate::colors::{ColorRGB, Colors};
+use crate::colors::{perceptual::ColorSpace, ColorRGB, Colors};
 
-pub fn generate_tetradic(color: ColorRGB) -> Colors {
+use super::{fit_to_size, HarmonyParams, SHADE_PARAMS};
+
+palette_generator!(Tetradic, "tetradic", "Four hues 90° apart, with a shade of the base", SHADE_PARAMS, generate_tetradic);
+
+pub fn generate_tetradic(color: ColorRGB, space: ColorSpace, params: &HarmonyParams) -> Colors {
     // need to find if the passed color is light or dark
-    let base_hsv = color.rgb_to_hsv();
-    let base_hue = base_hsv.0;
+    let base = space.to_polar(&color);
+    let base_hue = base.0;
 
     let mut palette = Vec::with_capacity(5);
 
@@ -14,25 +18,21 @@ pub fn generate_tetradic(color: ColorRGB) -> Colors {
     // Generate three additional colors, spaced 90 degrees apart
     for i in 0..3 {
         let hue = (base_hue + 90.0 * ((i + 1) as f64)) % 360.0;
-        let tetradic_color = ColorRGB::from_hsv((hue, base_hsv.1, base_hsv.2));
+        let tetradic_color = params.color(space, base, hue, base.2);
         palette.push(tetradic_color);
     }
 
     // Generate a lighter or darker shade as the fifth color
-    let additional_shade = if base_hsv.2 > 0.5 {
-        ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 0.8)) // Darker shade
+    let additional_shade = if base.2 > 0.5 {
+        params.color(space, base, base_hue, params.darker(base.2)) // Darker shade
     } else {
-        ColorRGB::from_hsv((base_hue, base_hsv.1, base_hsv.2 * 1.2)) // Lighter shade
+        params.color(space, base, base_hue, params.lighter(base.2)) // Lighter shade
     };
 
     palette.push(additional_shade);
 
-    Colors { 
-        background: palette[4].clone(), 
-        color_a: palette[0].clone(), 
-        color_b: palette[1].clone(), 
-        color_c: palette[2].clone(), 
-        highlight: palette[3].clone() }
+    let palette = vec![palette[4].clone(), palette[0].clone(), palette[1].clone(), palette[2].clone(), palette[3].clone()];
+    fit_to_size(palette, space, params)
 }
 
 
@@ -49,8 +49,8 @@ mod tests {
     // hsv: 179° 100% 93.3%
 
     let color = ColorRGB::from_hex("#00EEEC").unwrap();
-		let colors = generate_tetradic(color);
-		println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.background.color.to_string(), colors.color_a.color.to_string(), colors.color_b.color.to_string(), colors.color_c.color.to_string(), colors.highlight.color.to_string());
+		let colors = generate_tetradic(color, ColorSpace::Hsv, &HarmonyParams::default());
+		println!("0: {}, 1: {}, 2: {}, 3: {}, 4: {}", colors.color(0).color, colors.color(1).color, colors.color(2).color, colors.color(3).color, colors.color(4).color);
     //assert_eq!(hex, "#00EEEC".to_string());
   }  
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+//! Built-in named colors: the CSS named colors, the X11 `rgb.txt` names and the 195 most common of the
+//! 949 xkcd color survey names.
+//!
+//! Names are matched ignoring case, spaces, `-`, `_` and `'`; on a clash CSS wins over X11 and X11 over xkcd.
+//! A `css:`, `x11:` or `xkcd:` prefix picks the list, `xkcd:green` is not the same as `green`.
+
+use std::sync::OnceLock;
+
+use super::{perceptual::ciede2000, ColorRGB};
+
+static NAMED_COLORS: OnceLock<Vec<NamedColor>> = OnceLock::new();
+
+/// List a named color comes from.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NameSource {
+  #[default]
+  Css,
+  X11,
+  Xkcd,
+}
+
+impl NameSource {
+  pub const ALL: [NameSource; 3] = [NameSource::Css, NameSource::X11, NameSource::Xkcd];
+
+  /// Name as used in the `source:` prefix.
+  pub fn name(&self) -> &'static str {
+    match self {
+      NameSource::Css => "css",
+      NameSource::X11 => "x11",
+      NameSource::Xkcd => "xkcd",
+    }
+  }
+
+  fn data(&self) -> &'static str {
+    match self {
+      NameSource::Css => include_str!("names/css.txt"),
+      NameSource::X11 => include_str!("names/x11.txt"),
+      NameSource::Xkcd => include_str!("names/xkcd.txt"),
+    }
+  }
+}
+
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct NamedColor {
+  pub name: &'static str,
+  pub source: NameSource,
+  pub color: ColorRGB,
+  key: String, // normalized name
+  lab: (f64, f64, f64),
+}
+
+/// All named colors, CSS first, then X11, then xkcd.
+pub fn all() -> &'static [NamedColor] {
+  NAMED_COLORS.get_or_init(|| {
+    let mut colors = vec![];
+    for source in NameSource::ALL {
+      // `name<TAB>#rrggbb`, comment lines start with `!`, the xkcd list with its license
+      for line in source.data().lines().filter(|l| !l.starts_with('!') && !l.starts_with("License:")) {
+        let mut fields = line.split('\t').map(str::trim);
+        let (Some(name), Some(hex)) = (fields.next(), fields.next()) else {continue};
+        let Ok(color) = ColorRGB::from_hex(hex) else {continue};
+        let lab = color.rgb_to_lab();
+        colors.push(NamedColor { name, source, color, key: normalize(name), lab });
+      }
+    }
+    colors
+  })
+}
+
+fn normalize(name: &str) -> String {
+  name.to_lowercase().replace([' ', '-', '_', '\''], "")
+}
+
+/// Looks up a color by name, with an optional `css:`, `x11:` or `xkcd:` prefix.
+pub fn lookup(name: &str) -> Option<&'static NamedColor> {
+  let name = name.trim();
+  let (source, name) = match name.split_once(':') {
+    Some((prefix, rest)) => {(Some(NameSource::ALL.into_iter().find(|s| s.name() == prefix.trim().to_lowercase())?), rest)},
+    None => {(None, name)},
+  };
+  let key = normalize(name);
+  all().iter().find(|c| c.key == key && source.is_none_or(|s| c.source == s))
+}
+
+/// The named color closest to `color` and its CIEDE2000 difference.
+pub fn nearest(color: &ColorRGB) -> (&'static NamedColor, f64) {
+  let lab = color.rgb_to_lab();
+  all()
+    .iter()
+    .map(|c| (c, ciede2000(lab, c.lab)))
+    .min_by(|a, b| a.1.total_cmp(&b.1))
+    .expect("the named color lists are not empty")
+}
+
+/// The named colors closest to `color`, nearest first, skipping repeated names of the same color.
+pub fn nearest_n(color: &ColorRGB, n: usize) -> Vec<&'static NamedColor> {
+  let lab = color.rgb_to_lab();
+  let mut colors: Vec<(&NamedColor, f64)> = all().iter().map(|c| (c, ciede2000(lab, c.lab))).collect();
+  colors.sort_by(|a, b| a.1.total_cmp(&b.1));
+  dedup(colors.into_iter().map(|(c, _)| c), n)
+}
+
+/// Fuzzy search: the query's letters have to appear in order in the name.
+/// Exact matches come first, then prefixes, then substrings, then scattered matches with the fewest gaps.
+pub fn search(query: &str, limit: usize) -> Vec<&'static NamedColor> {
+  let query = normalize(query);
+  let mut matches: Vec<(usize, &NamedColor)> = all().iter().filter_map(|c| fuzzy_score(&query, &c.key).map(|score| (score, c))).collect();
+  // stable, so on a tie the list order (CSS, X11, xkcd) is kept
+  matches.sort_by_key(|(score, c)| (*score, c.key.len()));
+  dedup(matches.into_iter().map(|(_, c)| c), limit)
+}
+
+/// Skips names that were already listed with the same color, like the X11 copies of the CSS names.
+fn dedup(colors: impl Iterator<Item = &'static NamedColor>, limit: usize) -> Vec<&'static NamedColor> {
+  let mut listed: Vec<&NamedColor> = vec![];
+  for color in colors {
+    if listed.len() >= limit {break}
+    if !listed.iter().any(|c| c.key == color.key && c.color == color.color) {
+      listed.push(color);
+    }
+  }
+  listed
+}
+
+/// Lower is better, None if the query is no subsequence of the name.
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+  if query.is_empty() || name == query {
+    return Some(0);
+  }
+  if name.starts_with(query) {
+    return Some(1);
+  }
+  if let Some(pos) = name.find(query) {
+    return Some(2 + pos);
+  }
+  let mut gaps = 0;
+  let mut chars = name.char_indices();
+  let mut last: Option<usize> = None;
+  for q in query.chars() {
+    let (idx, _) = chars.find(|(_, c)| *c == q)?;
+    gaps += match last {
+      Some(last) => {idx - last - 1},
+      None => {idx},
+    };
+    last = Some(idx);
+  }
+  Some(100 + gaps)
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_lookup() {
+    assert_eq!(lookup("cornflowerblue").unwrap().color, ColorRGB::new(100, 149, 237));
+    assert_eq!(lookup("Cornflower Blue").unwrap().color, ColorRGB::new(100, 149, 237));
+    // CSS wins over X11 and xkcd, a prefix picks the list
+    assert_eq!(lookup("green").unwrap().color, ColorRGB::new(0, 128, 0));
+    assert_eq!(lookup("x11:green").unwrap().color, ColorRGB::new(0, 255, 0));
+    assert_eq!(lookup("xkcd:green").unwrap().color, ColorRGB::new(21, 176, 26));
+    assert_eq!(lookup("puke green").unwrap().source, NameSource::Xkcd);
+    assert_eq!(lookup("grey50").unwrap().source, NameSource::X11);
+    assert!(lookup("no such color").is_none());
+    assert!(lookup("foo:green").is_none());
+  }
+
+  #[test]
+  fn test_search() {
+    let names: Vec<&str> = search("cornflower", 3).iter().map(|c| c.name).collect();
+    assert_eq!(names, vec!["cornflowerblue", "cornflower blue"]);
+    assert_eq!(search("rbcpr", 1)[0].name, "rebeccapurple");
+    assert!(search("qqqq", 10).is_empty());
+    assert_eq!(search("", 5).len(), 5);
+  }
+
+  #[test]
+  fn test_nearest() {
+    let (named, delta) = nearest(&ColorRGB::new(101, 149, 236));
+    assert_eq!(named.name, "cornflowerblue");
+    assert!(delta < 1.0);
+    assert_eq!(nearest_n(&ColorRGB::new(255, 0, 0), 1)[0].name, "red");
+  }
+}
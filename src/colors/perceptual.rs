@@ -0,0 +1,519 @@
+//! Conversions to CIE XYZ, CIELAB, LCh, OKLab and OKLCH.
+//!
+//! XYZ is relative to the D65 white point with Y between 0. and 1.,
+//! CIELAB lightness is between 0. and 100. and OKLab lightness between 0. and 1.
+//! Hues of the polar forms (LCh, OKLCH) are in degrees between 0. and 360.
+
+use serde::{Deserialize, Serialize};
+
+use super::{contrast_ratio, ColorRGB};
+
+/// D65 reference white in XYZ.
+pub const D65_WHITE: (f64, f64, f64) = (0.95047, 1.0, 1.08883);
+
+/// D50 reference white in XYZ, used by CSS `lab()` and `lch()`.
+pub const D50_WHITE: (f64, f64, f64) = (0.96422, 1.0, 0.82521);
+
+/// Largest OKLCH chroma reached by any sRGB color, used to scale chroma sliders.
+pub const OKLCH_MAX_CHROMA: f64 = 0.37;
+
+/// sRGB transfer function, maps a channel between 0. and 1. to linear light.
+pub fn srgb_to_linear(c: f64) -> f64 {
+  if c <= 0.04045 {
+    c / 12.92
+  } else {
+    ((c + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+/// Inverse of [`srgb_to_linear`].
+pub fn linear_to_srgb(c: f64) -> f64 {
+  if c <= 0.0031308 {
+    12.92 * c
+  } else {
+    1.055 * c.powf(1.0 / 2.4) - 0.055
+  }
+}
+
+fn lab_f(t: f64) -> f64 {
+  const DELTA: f64 = 6.0 / 29.0;
+  if t > DELTA * DELTA * DELTA {
+    t.cbrt()
+  } else {
+    t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+  }
+}
+
+fn lab_f_inv(t: f64) -> f64 {
+  const DELTA: f64 = 6.0 / 29.0;
+  if t > DELTA {
+    t * t * t
+  } else {
+    3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+  }
+}
+
+/// Converts rectangular (l, a, b) to polar (l, chroma, hue in degrees).
+fn to_polar((l, a, b): (f64, f64, f64)) -> (f64, f64, f64) {
+  let chroma = (a * a + b * b).sqrt();
+  let hue = b.atan2(a).to_degrees();
+  (l, chroma, if hue < 0.0 { hue + 360.0 } else { hue })
+}
+
+fn from_polar((l, chroma, hue): (f64, f64, f64)) -> (f64, f64, f64) {
+  let (sin, cos) = hue.to_radians().sin_cos();
+  (l, chroma * cos, chroma * sin)
+}
+
+fn in_gamut((r, g, b): (f64, f64, f64)) -> bool {
+  const EPS: f64 = 1e-6;
+  [r, g, b].iter().all(|c| (-EPS..=1.0 + EPS).contains(c))
+}
+
+fn oklab_to_linear((l, a, b): (f64, f64, f64)) -> (f64, f64, f64) {
+  let l_ = l + 0.3963377774 * a + 0.2158037573 * b;
+  let m_ = l - 0.1055613458 * a - 0.0638541728 * b;
+  let s_ = l - 0.0894841775 * a - 1.2914855480 * b;
+
+  let l = l_ * l_ * l_;
+  let m = m_ * m_ * m_;
+  let s = s_ * s_ * s_;
+
+  (
+    4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+    -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+    -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+  )
+}
+
+impl ColorRGB {
+  /// sRGB channels in linear light between 0. and 1.
+  pub fn to_linear(&self) -> (f64, f64, f64) {
+    (
+      srgb_to_linear(f64::from(self.r) / 255.0),
+      srgb_to_linear(f64::from(self.g) / 255.0),
+      srgb_to_linear(f64::from(self.b) / 255.0),
+    )
+  }
+
+  /// Creates a color from linear light sRGB channels, clamping out of gamut values.
+  pub fn from_linear((r, g, b): (f64, f64, f64)) -> Self {
+    let to_channel = |c: f64| (linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0).round() as u8;
+    ColorRGB::new(to_channel(r), to_channel(g), to_channel(b))
+  }
+
+  /// Ref: http://www.brucelindbloom.com/index.html?Eqn_RGB_XYZ_Matrix.html
+  pub fn rgb_to_xyz(&self) -> (f64, f64, f64) {
+    let (r, g, b) = self.to_linear();
+    (
+      0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+      0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+      0.0193339 * r + 0.1191920 * g + 0.9503041 * b,
+    )
+  }
+
+  pub fn from_xyz((x, y, z): (f64, f64, f64)) -> Self {
+    ColorRGB::from_linear((
+      3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+      -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+      0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    ))
+  }
+
+  /// Ref: https://en.wikipedia.org/wiki/CIELAB_color_space#From_CIEXYZ_to_CIELAB
+  pub fn rgb_to_lab(&self) -> (f64, f64, f64) {
+    let (x, y, z) = self.rgb_to_xyz();
+    let fx = lab_f(x / D65_WHITE.0);
+    let fy = lab_f(y / D65_WHITE.1);
+    let fz = lab_f(z / D65_WHITE.2);
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+  }
+
+  pub fn from_lab((l, a, b): (f64, f64, f64)) -> Self {
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    ColorRGB::from_xyz((D65_WHITE.0 * lab_f_inv(fx), D65_WHITE.1 * lab_f_inv(fy), D65_WHITE.2 * lab_f_inv(fz)))
+  }
+
+  /// CIELAB relative to D50 as used by CSS, adapted to D65 with the Bradford transform.
+  ///
+  /// Ref: https://www.w3.org/TR/css-color-4/#color-conversion-code
+  pub fn from_lab_d50((l, a, b): (f64, f64, f64)) -> Self {
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let (x, y, z) = (D50_WHITE.0 * lab_f_inv(fx), D50_WHITE.1 * lab_f_inv(fy), D50_WHITE.2 * lab_f_inv(fz));
+    ColorRGB::from_xyz((
+      0.9554734 * x - 0.0230985 * y + 0.0632593 * z,
+      -0.0283697 * x + 1.0099954 * y + 0.0210414 * z,
+      0.0123140 * x - 0.0205076 * y + 1.3303659 * z,
+    ))
+  }
+
+  pub fn from_lch_d50(lch: (f64, f64, f64)) -> Self {
+    ColorRGB::from_lab_d50(from_polar(lch))
+  }
+
+  /// CIELAB in polar form: lightness, chroma and hue.
+  pub fn rgb_to_lch(&self) -> (f64, f64, f64) {
+    to_polar(self.rgb_to_lab())
+  }
+
+  pub fn from_lch(lch: (f64, f64, f64)) -> Self {
+    ColorRGB::from_lab(from_polar(lch))
+  }
+
+  /// Ref: https://bottosson.github.io/posts/oklab/
+  pub fn rgb_to_oklab(&self) -> (f64, f64, f64) {
+    let (r, g, b) = self.to_linear();
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    (
+      0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+      1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+      0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    )
+  }
+
+  pub fn from_oklab(lab: (f64, f64, f64)) -> Self {
+    ColorRGB::from_linear(oklab_to_linear(lab))
+  }
+
+  /// OKLab in polar form: lightness, chroma and hue.
+  pub fn rgb_to_oklch(&self) -> (f64, f64, f64) {
+    to_polar(self.rgb_to_oklab())
+  }
+
+  /// Lightness is clamped between 0. and 1. and chroma is reduced until the color fits into sRGB,
+  /// so hue and lightness are kept.
+  ///
+  /// Ref: https://www.w3.org/TR/css-color-4/#binsearch
+  pub fn from_oklch((l, c, h): (f64, f64, f64)) -> Self {
+    let l = l.clamp(0.0, 1.0);
+    let c = c.max(0.0);
+    if in_gamut(oklab_to_linear(from_polar((l, c, h)))) {
+      return ColorRGB::from_oklab(from_polar((l, c, h)));
+    }
+    let (mut low, mut high) = (0.0, c);
+    while high - low > 1e-4 {
+      let mid = (low + high) / 2.0;
+      if in_gamut(oklab_to_linear(from_polar((l, mid, h)))) {
+        low = mid;
+      } else {
+        high = mid;
+      }
+    }
+    ColorRGB::from_oklab(from_polar((l, low, h)))
+  }
+
+  pub fn with_oklch_lightness(&self, new_lightness: f64) -> Self {
+    let (_, c, h) = self.rgb_to_oklch();
+    ColorRGB::from_oklch((new_lightness, c, h)).with_alpha(self.a)
+  }
+
+  pub fn with_oklch_chroma(&self, new_chroma: f64) -> Self {
+    let (l, _, h) = self.rgb_to_oklch();
+    ColorRGB::from_oklch((l, new_chroma, h)).with_alpha(self.a)
+  }
+
+  pub fn with_oklch_hue(&self, new_hue: f64) -> Self {
+    let (l, c, _) = self.rgb_to_oklch();
+    ColorRGB::from_oklch((l, c, new_hue)).with_alpha(self.a)
+  }
+
+  pub fn shift_oklch_lightness(&self, amount: f64) -> Self {
+    let (l, c, h) = self.rgb_to_oklch();
+    ColorRGB::from_oklch(((l + amount).clamp(0.0, 1.0), c, h)).with_alpha(self.a)
+  }
+
+  pub fn shift_oklch_chroma(&self, amount: f64) -> Self {
+    let (l, c, h) = self.rgb_to_oklch();
+    ColorRGB::from_oklch((l, (c + amount).clamp(0.0, OKLCH_MAX_CHROMA), h)).with_alpha(self.a)
+  }
+
+  pub fn shift_oklch_hue(&self, amount: f64) -> Self {
+    let (l, c, h) = self.rgb_to_oklch();
+    ColorRGB::from_oklch((l, c, (h + amount).rem_euclid(360.0))).with_alpha(self.a)
+  }
+}
+
+/// CIEDE2000 color difference between two CIELAB colors.
+///
+/// Ref: Sharma, Wu, Dalal (2005), https://hajim.rochester.edu/ece/sites/gsharma/ciede2000/
+pub fn ciede2000((l1, a1, b1): (f64, f64, f64), (l2, a2, b2): (f64, f64, f64)) -> f64 {
+  let pow25_7 = 25f64.powi(7);
+  let c_bar = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+  let g = 0.5 * (1.0 - (c_bar.powi(7) / (c_bar.powi(7) + pow25_7)).sqrt());
+  let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+  let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+  let hue = |a: f64, b: f64| if a == 0.0 && b == 0.0 {0.0} else {b.atan2(a).to_degrees().rem_euclid(360.0)};
+  let (h1, h2) = (hue(a1, b1), hue(a2, b2));
+
+  let delta_l = l2 - l1;
+  let delta_c = c2 - c1;
+  let delta_h = if c1 * c2 == 0.0 {
+    0.0
+  } else if h2 - h1 > 180.0 {
+    h2 - h1 - 360.0
+  } else if h2 - h1 < -180.0 {
+    h2 - h1 + 360.0
+  } else {
+    h2 - h1
+  };
+  let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+  let l_bar = (l1 + l2) / 2.0;
+  let c_bar = (c1 + c2) / 2.0;
+  let h_bar = if c1 * c2 == 0.0 {
+    h1 + h2
+  } else if (h1 - h2).abs() <= 180.0 {
+    (h1 + h2) / 2.0
+  } else if h1 + h2 < 360.0 {
+    (h1 + h2 + 360.0) / 2.0
+  } else {
+    (h1 + h2 - 360.0) / 2.0
+  };
+
+  let cos = |deg: f64| deg.to_radians().cos();
+  let t = 1.0 - 0.17 * cos(h_bar - 30.0) + 0.24 * cos(2.0 * h_bar) + 0.32 * cos(3.0 * h_bar + 6.0) - 0.20 * cos(4.0 * h_bar - 63.0);
+  let delta_theta = 30.0 * (-((h_bar - 275.0) / 25.0).powi(2)).exp();
+  let r_c = 2.0 * (c_bar.powi(7) / (c_bar.powi(7) + pow25_7)).sqrt();
+  let s_l = 1.0 + 0.015 * (l_bar - 50.0).powi(2) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+  let s_c = 1.0 + 0.045 * c_bar;
+  let s_h = 1.0 + 0.015 * c_bar * t;
+  let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+  let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+  (l * l + c * c + h * h + r_t * c * h).sqrt()
+}
+
+/// Color difference formula used to find near-duplicate colors.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum DeltaE {
+  /// CIEDE2000 in CIELAB, about 2.3 is a just noticeable difference
+  #[default]
+  Ciede2000,
+  /// Euclidean distance in OKLab, about 0.02 is a just noticeable difference
+  Ok,
+}
+
+impl DeltaE {
+  pub fn name(&self) -> &'static str {
+    match self {
+      DeltaE::Ciede2000 => "ΔE00",
+      DeltaE::Ok => "ΔEok",
+    }
+  }
+
+  pub fn distance(&self, x: &ColorRGB, y: &ColorRGB) -> f64 {
+    match self {
+      DeltaE::Ciede2000 => x.delta_e_2000(y),
+      DeltaE::Ok => x.delta_e_ok(y),
+    }
+  }
+
+  /// Distance below which two palette slots are hard to tell apart at a glance.
+  pub fn default_threshold(&self) -> f64 {
+    match self {
+      DeltaE::Ciede2000 => 5.0,
+      DeltaE::Ok => 0.05,
+    }
+  }
+}
+
+impl ColorRGB {
+  pub fn delta_e_2000(&self, other: &ColorRGB) -> f64 {
+    ciede2000(self.rgb_to_lab(), other.rgb_to_lab())
+  }
+
+  /// Ref: https://www.w3.org/TR/css-color-4/#color-difference-OK
+  pub fn delta_e_ok(&self, other: &ColorRGB) -> f64 {
+    let (l1, a1, b1) = self.rgb_to_oklab();
+    let (l2, a2, b2) = other.rgb_to_oklab();
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+  }
+
+  /// Nearest color that reaches a WCAG 2 contrast ratio against `background`, found by moving
+  /// OKLCH lightness up or down while keeping hue and chroma (as far as sRGB allows).
+  /// Returns the color itself if it already passes, or None if no lightness reaches the target.
+  pub fn with_contrast_against(&self, background: &ColorRGB, target_ratio: f64) -> Option<ColorRGB> {
+    const STEP: f64 = 0.001;
+    if contrast_ratio(self, background) >= target_ratio {
+      return Some(self.clone());
+    }
+    let (l, c, h) = self.rgb_to_oklch();
+    let search = |direction: f64| {
+      let mut lightness = l;
+      while (0.0..=1.0).contains(&lightness) {
+        let candidate = ColorRGB::from_oklch((lightness, c, h)).with_alpha(self.a);
+        if contrast_ratio(&candidate, background) >= target_ratio {
+          return Some((candidate, (lightness - l).abs()));
+        }
+        lightness += direction * STEP;
+      }
+      None
+    };
+    match (search(1.0), search(-1.0)) {
+      (Some(lighter), Some(darker)) => Some(if lighter.1 <= darker.1 {lighter.0} else {darker.0}),
+      (Some((color, _)), None) | (None, Some((color, _))) => Some(color),
+      (None, None) => None,
+    }
+  }
+}
+
+/// Space the generators rotate hues and scale lightness in.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+  /// Hue, saturation and value
+  #[default]
+  Hsv,
+  /// Perceptual OKLab in polar form: hue, chroma and lightness
+  Oklch,
+}
+
+impl ColorSpace {
+  /// Splits a color into hue, a saturation-like and a lightness-like component.
+  /// For HSV that is (h, s, v), for OKLCH (h, c, l).
+  pub fn to_polar(&self, color: &ColorRGB) -> (f64, f64, f64) {
+    match self {
+      ColorSpace::Hsv => color.rgb_to_hsv(),
+      ColorSpace::Oklch => {
+        let (l, c, h) = color.rgb_to_oklch();
+        (h, c, l)
+      },
+    }
+  }
+
+  /// Inverse of [`ColorSpace::to_polar`].
+  pub fn from_polar(&self, (h, s, l): (f64, f64, f64)) -> ColorRGB {
+    match self {
+      ColorSpace::Hsv => ColorRGB::from_hsv((h.rem_euclid(360.0), s, l)),
+      ColorSpace::Oklch => ColorRGB::from_oklch((l, s, h.rem_euclid(360.0))),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      ColorSpace::Hsv => "HSV",
+      ColorSpace::Oklch => "OKLCH",
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(actual: (f64, f64, f64), expected: (f64, f64, f64), tolerance: f64) {
+    let close = (actual.0 - expected.0).abs() < tolerance
+      && (actual.1 - expected.1).abs() < tolerance
+      && (actual.2 - expected.2).abs() < tolerance;
+    assert!(close, "{:?} is not within {} of {:?}", actual, tolerance, expected);
+  }
+
+  #[test]
+  fn test_xyz_reference() {
+    // http://www.brucelindbloom.com/index.html?ColorCalculator.html
+    assert_close(ColorRGB::new(255, 0, 0).rgb_to_xyz(), (0.4125, 0.2127, 0.0193), 1e-4);
+    assert_close(ColorRGB::new(255, 255, 255).rgb_to_xyz(), D65_WHITE, 1e-3);
+  }
+
+  #[test]
+  fn test_lab_reference() {
+    // https://colorjs.io/apps/convert/?color=rgb(255,0,0)
+    assert_close(ColorRGB::new(255, 0, 0).rgb_to_lab(), (53.24, 80.09, 67.20), 0.02);
+    assert_close(ColorRGB::new(0, 255, 255).rgb_to_lab(), (91.12, -48.08, -14.14), 0.02);
+    assert_close(ColorRGB::new(255, 255, 255).rgb_to_lab(), (100.0, 0.0, 0.0), 0.01);
+  }
+
+  #[test]
+  fn test_lch_reference() {
+    assert_close(ColorRGB::new(255, 0, 0).rgb_to_lch(), (53.24, 104.55, 40.0), 0.02);
+  }
+
+  #[test]
+  fn test_oklab_reference() {
+    // https://bottosson.github.io/posts/oklab/
+    assert_close(ColorRGB::new(255, 0, 0).rgb_to_oklab(), (0.62796, 0.22486, 0.12585), 1e-4);
+    assert_close(ColorRGB::new(255, 255, 255).rgb_to_oklab(), (1.0, 0.0, 0.0), 1e-4);
+  }
+
+  #[test]
+  fn test_oklch_reference() {
+    assert_close(ColorRGB::new(255, 0, 0).rgb_to_oklch(), (0.62796, 0.25768, 29.234), 1e-3);
+  }
+
+  #[test]
+  fn test_roundtrips() {
+    let color = ColorRGB::new(144, 76, 98);
+    assert_eq!(ColorRGB::from_xyz(color.rgb_to_xyz()), color);
+    assert_eq!(ColorRGB::from_lab(color.rgb_to_lab()), color);
+    assert_eq!(ColorRGB::from_lch(color.rgb_to_lch()), color);
+    assert_eq!(ColorRGB::from_oklab(color.rgb_to_oklab()), color);
+    assert_eq!(ColorRGB::from_oklch(color.rgb_to_oklch()), color);
+  }
+
+  #[test]
+  fn test_oklch_gamut_mapping_keeps_hue() {
+    let color = ColorRGB::from_oklch((0.7, 0.37, 150.0));
+    let (l, c, h) = color.rgb_to_oklch();
+    assert!((l - 0.7).abs() < 0.01, "{}", l);
+    assert!(c < 0.37);
+    assert!((h - 150.0).abs() < 1.5, "{}", h);
+  }
+
+  #[test]
+  fn test_ciede2000_reference() {
+    // Sharma et al., test data pairs 1, 7, 17 and 25
+    let pairs = [
+      ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+      ((50.0, 0.0, 0.0), (50.0, -1.0, 2.0), 2.3669),
+      ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+      ((60.2574, -34.0099, 36.2677), (60.4626, -34.1751, 39.4387), 1.2644),
+    ];
+    for (lab1, lab2, expected) in pairs {
+      assert!((ciede2000(lab1, lab2) - expected).abs() < 1e-4, "{:?} {:?}", lab1, lab2);
+      assert!((ciede2000(lab2, lab1) - expected).abs() < 1e-4, "{:?} {:?}", lab2, lab1);
+    }
+  }
+
+  #[test]
+  fn test_delta_e_ok() {
+    let black = ColorRGB::new(0, 0, 0);
+    let white = ColorRGB::new(255, 255, 255);
+    assert!((black.delta_e_ok(&white) - 1.0).abs() < 1e-4);
+    assert_eq!(white.delta_e_ok(&white), 0.0);
+    assert_eq!(white.delta_e_2000(&white), 0.0);
+  }
+
+  #[test]
+  fn test_with_contrast_against() {
+    let white = ColorRGB::new(255, 255, 255);
+    let grey = ColorRGB::new(119, 119, 119);
+    let fixed = grey.with_contrast_against(&white, 4.5).unwrap();
+    assert!(contrast_ratio(&fixed, &white) >= 4.5);
+    assert!(fixed.r < grey.r && fixed.r >= 116, "{:?}", fixed);
+
+    let dark = ColorRGB::new(32, 32, 32);
+    let color = ColorRGB::new(144, 72, 93);
+    let fixed = color.with_contrast_against(&dark, 7.0).unwrap();
+    assert!(contrast_ratio(&fixed, &dark) >= 7.0);
+    assert!(fixed.rgb_to_oklch().0 > color.rgb_to_oklch().0);
+    assert!((fixed.rgb_to_oklch().2 - color.rgb_to_oklch().2).abs() < 2.0);
+
+    assert_eq!(white.with_contrast_against(&dark, 4.5), Some(white.clone()));
+    assert_eq!(grey.with_contrast_against(&grey, 7.0), None);
+  }
+
+  #[test]
+  fn test_color_space_polar_roundtrip() {
+    let color = ColorRGB::new(26, 97, 127);
+    assert_eq!(ColorSpace::Oklch.from_polar(ColorSpace::Oklch.to_polar(&color)), color);
+    // HSV truncates the channels on the way back
+    let hsv = ColorSpace::Hsv.from_polar(ColorSpace::Hsv.to_polar(&color));
+    assert!([(hsv.r, color.r), (hsv.g, color.g), (hsv.b, color.b)].iter().all(|&(x, y)| x.abs_diff(y) <= 1), "{:?}", hsv);
+  }
+}
@@ -0,0 +1,309 @@
+//! Semantic roles (`fg`, `bg`, `accent`, `error`, ...) on top of the palette slots.
+//!
+//! A role points to a slot by name, optionally shaded: `highlight -20%` is the highlight 20% darker,
+//! `background +10%` the background 10% brighter (see [`ColorRGB::shade`]).
+//! Roles that are not set, or whose slot is gone, follow a default derived from the palette.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{relative_luminance, ColorRGB, Colors};
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+  #[default]
+  Fg,
+  Bg,
+  Surface,
+  Border,
+  Accent,
+  Error,
+  Warning,
+  Success,
+  Selection,
+  Comment,
+}
+
+impl Role {
+  pub const ALL: [Role; 10] = [
+    Role::Fg,
+    Role::Bg,
+    Role::Surface,
+    Role::Border,
+    Role::Accent,
+    Role::Error,
+    Role::Warning,
+    Role::Success,
+    Role::Selection,
+    Role::Comment,
+  ];
+
+  /// Name as used in palette files and exports.
+  pub fn name(&self) -> &'static str {
+    match self {
+      Role::Fg => "fg",
+      Role::Bg => "bg",
+      Role::Surface => "surface",
+      Role::Border => "border",
+      Role::Accent => "accent",
+      Role::Error => "error",
+      Role::Warning => "warning",
+      Role::Success => "success",
+      Role::Selection => "selection",
+      Role::Comment => "comment",
+    }
+  }
+
+  /// Where the role points when it is not set.
+  /// Surfaces and borders are the background moved away from black or white, the selection is the accent moved
+  /// toward the background and comments the foreground; error, warning and success take a slot of that name
+  /// (or `red`, `yellow`, `green`), else the foreground nearest in OKLCH hue.
+  pub fn default_source(&self, colors: &Colors) -> RoleSource {
+    // +1 brightens on a dark background, -1 darkens on a light one
+    let away: i8 = if relative_luminance(colors.background()) < 0.18 {1} else {-1};
+    let fg = if colors.by_name("foreground").is_some() {"foreground"} else {colors.name(1)};
+    match self {
+      Role::Fg => {RoleSource::new(fg, 0)},
+      Role::Bg => {RoleSource::new(colors.name(0), 0)},
+      Role::Surface => {RoleSource::new(colors.name(0), 10 * away)},
+      Role::Border => {RoleSource::new(colors.name(0), 30 * away)},
+      Role::Accent => {RoleSource::new(colors.accent_name(), 0)},
+      Role::Error => {RoleSource::new(nearest_hue(colors, &["error", "red"], 29.0), 0)},
+      Role::Warning => {RoleSource::new(nearest_hue(colors, &["warning", "yellow"], 110.0), 0)},
+      Role::Success => {RoleSource::new(nearest_hue(colors, &["success", "green"], 142.0), 0)},
+      Role::Selection => {RoleSource::new(colors.accent_name(), -50 * away)},
+      Role::Comment => {RoleSource::new(fg, -40 * away)},
+    }
+  }
+}
+
+impl fmt::Display for Role {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+/// The first slot named like one of `names`, else the colorful foreground nearest to the OKLCH `hue`, else the accent.
+fn nearest_hue<'a>(colors: &'a Colors, names: &[&str], hue: f64) -> &'a str {
+  if let Some(slot) = colors.slots().iter().find(|s| names.contains(&s.name.as_str())) {
+    return &slot.name;
+  }
+  let distance = |h: f64| {
+    let d = (h - hue).rem_euclid(360.0);
+    d.min(360.0 - d)
+  };
+  colors.slots()[1..]
+    .iter()
+    .map(|s| (s, s.color.rgb_to_oklch()))
+    .filter(|(_, (_, chroma, _))| *chroma >= 0.05)
+    .min_by(|(_, (_, _, a)), (_, (_, _, b))| distance(*a).total_cmp(&distance(*b)))
+    .map(|(s, _)| s.name.as_str())
+    .unwrap_or(colors.accent_name())
+}
+
+/// A slot by name, shaded by a percentage from -100 (black) to +100 (white).
+/// Written as `slot`, `slot +20%` or `slot -20%`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RoleSource {
+  pub slot: String,
+  pub shade: i8,
+}
+
+impl RoleSource {
+  pub fn new(slot: &str, shade: i8) -> Self {
+    RoleSource { slot: slot.to_string(), shade: shade.clamp(-100, 100) }
+  }
+
+  /// The slot's color, shaded; None if the palette has no such slot.
+  pub fn resolve(&self, colors: &Colors) -> Option<ColorRGB> {
+    let color = colors.by_name(&self.slot)?;
+    if self.shade == 0 {
+      return Some(color.clone());
+    }
+    let shaded = ColorRGB::from_color(color.shade(self.shade as f32 / 100.0)).ok()?;
+    Some(shaded.with_alpha(color.a))
+  }
+}
+
+impl fmt::Display for RoleSource {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.shade {
+      0 => write!(f, "{}", self.slot),
+      shade => write!(f, "{} {:+}%", self.slot, shade),
+    }
+  }
+}
+
+impl FromStr for RoleSource {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parts = s.split_whitespace();
+    let slot = parts.next().ok_or("missing the color name")?;
+    let shade = match parts.next() {
+      Some(shade) => {
+        let percent = shade.strip_suffix('%').ok_or(format!("invalid shade `{}`, expected e.g. -20%", shade))?;
+        let percent: i8 = percent.parse().map_err(|_| format!("invalid shade `{}`, expected e.g. -20%", shade))?;
+        if !(-100..=100).contains(&percent) {
+          return Err(format!("shade `{}` is not within -100% and +100%", shade));
+        }
+        percent
+      },
+      None => {0},
+    };
+    if let Some(rest) = parts.next() {
+      return Err(format!("unexpected `{}` after the shade", rest));
+    }
+    Ok(RoleSource::new(slot, shade))
+  }
+}
+
+impl TryFrom<String> for RoleSource {
+  type Error = String;
+
+  fn try_from(s: String) -> Result<Self, Self::Error> {
+    s.parse()
+  }
+}
+
+impl From<RoleSource> for String {
+  fn from(source: RoleSource) -> String {
+    source.to_string()
+  }
+}
+
+/// The roles that were set, the others follow [`Role::default_source`].
+#[derive(Default, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Roles(BTreeMap<Role, RoleSource>);
+
+impl Roles {
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  pub fn set(&mut self, role: Role, source: RoleSource) {
+    self.0.insert(role, source);
+  }
+
+  /// Back to the default.
+  pub fn reset(&mut self, role: Role) {
+    self.0.remove(&role);
+  }
+
+  /// Whether the role is set to a slot that `colors` has.
+  pub fn is_set(&self, role: Role, colors: &Colors) -> bool {
+    self.0.get(&role).is_some_and(|source| colors.by_name(&source.slot).is_some())
+  }
+
+  pub fn source(&self, role: Role, colors: &Colors) -> RoleSource {
+    match self.0.get(&role) {
+      Some(source) if colors.by_name(&source.slot).is_some() => {source.clone()},
+      _ => {role.default_source(colors)},
+    }
+  }
+
+  pub fn color(&self, role: Role, colors: &Colors) -> ColorRGB {
+    self.source(role, colors).resolve(colors).expect("roles point to slots of the palette")
+  }
+
+  /// Every role with its color, in [`Role::ALL`] order.
+  pub fn resolved(&self, colors: &Colors) -> Vec<(Role, ColorRGB)> {
+    Role::ALL.into_iter().map(|role| (role, self.color(role, colors))).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::colors::Slot;
+
+  fn test_colors() -> Colors {
+    Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(144, 72, 93),
+      ColorRGB::new(26, 97, 127),
+      ColorRGB::new(72, 220, 3),
+    ])
+  }
+
+  #[test]
+  fn test_role_source() {
+    assert_eq!("highlight -20%".parse::<RoleSource>(), Ok(RoleSource::new("highlight", -20)));
+    assert_eq!("color_a".parse::<RoleSource>(), Ok(RoleSource::new("color_a", 0)));
+    assert_eq!(" background  +10% ".parse::<RoleSource>(), Ok(RoleSource::new("background", 10)));
+    assert!("highlight -20".parse::<RoleSource>().is_err());
+    assert!("highlight -120%".parse::<RoleSource>().is_err());
+    assert!("".parse::<RoleSource>().is_err());
+    assert_eq!(RoleSource::new("highlight", -20).to_string(), "highlight -20%");
+    assert_eq!(RoleSource::new("background", 10).to_string(), "background +10%");
+    assert_eq!(RoleSource::new("color_a", 0).to_string(), "color_a");
+  }
+
+  #[test]
+  fn test_resolve() {
+    let colors = test_colors();
+    assert_eq!(RoleSource::new("highlight", 0).resolve(&colors), Some(ColorRGB::new(72, 220, 3)));
+    assert_eq!(RoleSource::new("background", -50).resolve(&colors), Some(ColorRGB::new(16, 16, 16)));
+    assert_eq!(RoleSource::new("background", 100).resolve(&colors), Some(ColorRGB::new(255, 255, 255)));
+    assert_eq!(RoleSource::new("warning", 0).resolve(&colors), None);
+  }
+
+  #[test]
+  fn test_defaults() {
+    let colors = test_colors();
+    let roles = Roles::default();
+    assert_eq!(roles.source(Role::Bg, &colors), RoleSource::new("background", 0));
+    assert_eq!(roles.source(Role::Fg, &colors), RoleSource::new("color_a", 0));
+    assert_eq!(roles.source(Role::Accent, &colors), RoleSource::new("highlight", 0));
+    // dark background: surfaces are brighter, the selection darker
+    assert_eq!(roles.source(Role::Surface, &colors), RoleSource::new("background", 10));
+    assert_eq!(roles.source(Role::Selection, &colors), RoleSource::new("highlight", -50));
+    assert_eq!(roles.source(Role::Error, &colors), RoleSource::new("color_b", 0));
+    assert_eq!(roles.source(Role::Success, &colors), RoleSource::new("highlight", 0));
+    let light = colors.with_color(0, ColorRGB::new(240, 240, 240));
+    assert_eq!(roles.source(Role::Surface, &light), RoleSource::new("background", -10));
+    // a slot of the role's name wins
+    let named = Colors::new(vec![
+      Slot::new("background", ColorRGB::new(32, 32, 32)),
+      Slot::new("color_a", ColorRGB::new(255, 255, 255)),
+      Slot::new("warning", ColorRGB::new(255, 170, 0)),
+    ]).unwrap();
+    assert_eq!(roles.source(Role::Warning, &named), RoleSource::new("warning", 0));
+    assert_eq!(roles.resolved(&colors).len(), Role::ALL.len());
+  }
+
+  #[test]
+  fn test_roles() {
+    let colors = test_colors();
+    let mut roles = Roles::default();
+    roles.set(Role::Accent, RoleSource::new("color_c", -20));
+    assert!(roles.is_set(Role::Accent, &colors));
+    assert_eq!(roles.color(Role::Accent, &colors), ColorRGB::new(21, 78, 102));
+    // a removed slot falls back to the default
+    let mut fewer = colors.clone();
+    fewer.remove(3).unwrap();
+    assert!(!roles.is_set(Role::Accent, &fewer));
+    assert_eq!(roles.color(Role::Accent, &fewer), ColorRGB::new(72, 220, 3));
+    roles.reset(Role::Accent);
+    assert!(roles.is_empty());
+  }
+
+  #[test]
+  fn test_serde() {
+    let mut roles = Roles::default();
+    roles.set(Role::Comment, RoleSource::new("color_a", -40));
+    roles.set(Role::Fg, RoleSource::new("color_a", 0));
+    let s = serde_json::to_string(&roles).unwrap();
+    assert_eq!(s, r#"{"fg":"color_a","comment":"color_a -40%"}"#);
+    assert_eq!(serde_json::from_str::<Roles>(&s).unwrap(), roles);
+    assert!(serde_json::from_str::<Roles>(r#"{"fg":"color_a 20"}"#).is_err());
+    assert!(serde_json::from_str::<Roles>(r#"{"text":"color_a"}"#).is_err());
+  }
+}
@@ -0,0 +1,231 @@
+//! Versioned on-disk schema for palettes.
+//!
+//! A palette document stores every slot of [`Colors`] as a `#RRGGBB` hex string under its name,
+//! in palette order, plus optional metadata about where the palette came from:
+//!
+//! ```toml
+//! version = 2
+//! name = "dusk"
+//! harmony = "triadic"       # optional: name of a registered generator, see
+//!                           # `termcolors generators`
+//! base = "#00EEEC"          # optional: color the harmony was generated from
+//!
+//! [colors]
+//! background = "#202020"
+//! color_a = "#FFFFFF"
+//! color_b = "#90485D"
+//! color_c = "#1A617F"
+//! highlight = "#48DC03"
+//! warning = "#FFAA00"       # any further slots, 2 to 32 in total
+//!
+//! [roles]                   # optional: semantic roles, see [`super::roles`]
+//! accent = "highlight"
+//! selection = "highlight -40%"
+//! ```
+//!
+//! The same fields are used for JSON and YAML. `version` is bumped whenever a change
+//! would make older readers misinterpret a document; documents with a newer version
+//! than [`SCHEMA_VERSION`] are rejected. Version 1 documents have exactly the five slots above
+//! and are read as they are.
+
+use std::path::Path;
+
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{generators::Harmony, roles::Roles, ColorRGB, Colors};
+
+/// Current version of the palette document schema.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PaletteFormat {
+  #[default]
+  Json,
+  Toml,
+  Yaml,
+}
+
+impl PaletteFormat {
+  /// Picks the format from a file extension, e.g. `palette.toml`.
+  pub fn from_path(path: &Path) -> Option<Self> {
+    match path.extension()?.to_str()?.to_lowercase().as_ref() {
+      "json" => Some(PaletteFormat::Json),
+      "toml" => Some(PaletteFormat::Toml),
+      "yaml" | "yml" => Some(PaletteFormat::Yaml),
+      _ => None,
+    }
+  }
+
+  pub fn extension(&self) -> &'static str {
+    match self {
+      PaletteFormat::Json => "json",
+      PaletteFormat::Toml => "toml",
+      PaletteFormat::Yaml => "yaml",
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PaletteDocument {
+  pub version: u32,
+  #[serde(default)]
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub harmony: Option<Harmony>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub base: Option<ColorRGB>,
+  pub colors: Colors,
+  #[serde(default, skip_serializing_if = "Roles::is_empty")]
+  pub roles: Roles,
+}
+
+impl Default for PaletteDocument {
+  fn default() -> Self {
+    Self::new("", Colors::default())
+  }
+}
+
+impl PaletteDocument {
+  pub fn new(name: &str, colors: Colors) -> Self {
+    PaletteDocument { version: SCHEMA_VERSION, name: name.to_string(), harmony: None, base: None, colors, roles: Roles::default() }
+  }
+
+  pub fn with_harmony(mut self, harmony: Harmony, base: ColorRGB) -> Self {
+    self.harmony = Some(harmony);
+    self.base = Some(base);
+    self
+  }
+
+  pub fn with_roles(mut self, roles: Roles) -> Self {
+    self.roles = roles;
+    self
+  }
+
+  pub fn to_string_as(&self, format: PaletteFormat) -> Result<String> {
+    let s = match format {
+      PaletteFormat::Json => serde_json::to_string_pretty(self)?,
+      PaletteFormat::Toml => toml::to_string(self)?,
+      PaletteFormat::Yaml => serde_yaml::to_string(self)?,
+    };
+    Ok(s)
+  }
+
+  pub fn parse(s: &str, format: PaletteFormat) -> Result<Self> {
+    let doc: Self = match format {
+      PaletteFormat::Json => serde_json::from_str(s)?,
+      PaletteFormat::Toml => toml::from_str(s)?,
+      PaletteFormat::Yaml => serde_yaml::from_str(s)?,
+    };
+    if doc.version > SCHEMA_VERSION {
+      return Err(eyre!("Palette schema version {} is newer than the supported version {}", doc.version, SCHEMA_VERSION));
+    }
+    Ok(doc)
+  }
+
+  /// Reads a document, choosing the format by file extension.
+  pub fn read(path: &Path) -> Result<Self> {
+    let format = PaletteFormat::from_path(path).ok_or_else(|| eyre!("Unknown palette format for {}", path.display()))?;
+    let content = std::fs::read_to_string(path).map_err(|e| eyre!("Could not read {}: {}", path.display(), e))?;
+    Self::parse(&content, format).map_err(|e| eyre!("Could not parse {}: {}", path.display(), e))
+  }
+
+  /// Writes a document, choosing the format by file extension.
+  pub fn write(&self, path: &Path) -> Result<()> {
+    let format = PaletteFormat::from_path(path).ok_or_else(|| eyre!("Unknown palette format for {}", path.display()))?;
+    std::fs::write(path, self.to_string_as(format)?)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::colors::roles::{Role, RoleSource};
+
+  fn test_document() -> PaletteDocument {
+    let colors = Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(144, 72, 93),
+      ColorRGB::new(26, 97, 127),
+      ColorRGB::new(72, 220, 3),
+    ]);
+    let mut roles = Roles::default();
+    roles.set(Role::Selection, RoleSource::new("highlight", -40));
+    PaletteDocument::new("dusk", colors).with_harmony(Harmony::SPLIT_COMPLEMENTARY, ColorRGB::new(0, 238, 236)).with_roles(roles)
+  }
+
+  #[test]
+  fn test_roundtrip_json() -> Result<()> {
+    let doc = test_document();
+    let s = doc.to_string_as(PaletteFormat::Json)?;
+    assert_eq!(PaletteDocument::parse(&s, PaletteFormat::Json)?, doc);
+    Ok(())
+  }
+
+  #[test]
+  fn test_roundtrip_toml() -> Result<()> {
+    let doc = test_document();
+    let s = doc.to_string_as(PaletteFormat::Toml)?;
+    assert_eq!(PaletteDocument::parse(&s, PaletteFormat::Toml)?, doc);
+    Ok(())
+  }
+
+  #[test]
+  fn test_roundtrip_yaml() -> Result<()> {
+    let doc = test_document();
+    let s = doc.to_string_as(PaletteFormat::Yaml)?;
+    assert_eq!(PaletteDocument::parse(&s, PaletteFormat::Yaml)?, doc);
+    Ok(())
+  }
+
+  #[test]
+  fn test_json_layout() -> Result<()> {
+    let s = test_document().to_string_as(PaletteFormat::Json)?;
+    let value: serde_json::Value = serde_json::from_str(&s)?;
+    assert_eq!(value["version"], 2);
+    assert_eq!(value["harmony"], "split_complementary");
+    assert_eq!(value["base"], "#00EEEC");
+    assert_eq!(value["colors"]["color_b"], "#90485D");
+    assert_eq!(value["roles"]["selection"], "highlight -40%");
+    Ok(())
+  }
+
+  #[test]
+  fn test_optional_metadata() -> Result<()> {
+    let s = r##"{"version": 1, "colors": {"background": "#202020", "color_a": "#ffffff", "color_b": "#90485D", "color_c": "#1A617F", "highlight": "#48DC03"}}"##;
+    let doc = PaletteDocument::parse(s, PaletteFormat::Json)?;
+    assert_eq!(doc.name, "");
+    assert_eq!(doc.harmony, None);
+    assert!(doc.roles.is_empty());
+    assert_eq!(doc.colors.by_name("color_a"), Some(&ColorRGB::new(255, 255, 255)));
+    Ok(())
+  }
+
+  #[test]
+  fn test_named_slots() -> Result<()> {
+    let s = "version = 2\n[colors]\ncolor_a = \"#FFFFFF\"\nbackground = \"#202020\"\nwarning = \"#FFAA00\"\n";
+    let doc = PaletteDocument::parse(s, PaletteFormat::Toml)?;
+    let names: Vec<&str> = doc.colors.named_slots().iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, vec!["background", "color_a", "warning"]);
+    assert_eq!(PaletteDocument::parse(&doc.to_string_as(PaletteFormat::Yaml)?, PaletteFormat::Yaml)?, doc);
+    let s = "version = 2\n[colors]\nbackground = \"#202020\"\n";
+    assert!(PaletteDocument::parse(s, PaletteFormat::Toml).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_rejects_newer_version() {
+    let s = "version = 99\n[colors]\nbackground = \"#202020\"\ncolor_a = \"#FFFFFF\"\ncolor_b = \"#90485D\"\ncolor_c = \"#1A617F\"\nhighlight = \"#48DC03\"\n";
+    assert!(PaletteDocument::parse(s, PaletteFormat::Toml).is_err());
+  }
+
+  #[test]
+  fn test_rejects_invalid_hex() {
+    let s = "version: 1\ncolors:\n  background: '#20202'\n  color_a: '#FFFFFF'\n  color_b: '#90485D'\n  color_c: '#1A617F'\n  highlight: '#48DC03'\n";
+    assert!(PaletteDocument::parse(s, PaletteFormat::Yaml).is_err());
+  }
+}
@@ -0,0 +1,186 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use color_eyre::eyre::Result;
+
+use crate::{
+  cli::{ColorModel, Command},
+  colors::{generators::{generate_palette_with_harmony, registry, HarmonyParams}, perceptual::DeltaE, schema::PaletteDocument, ColorRGB, Colors, Cvd},
+  export::{export_palette, export_roles},
+  palettes::PaletteStore,
+};
+
+/// Runs a headless command, writing its output to `out`.
+pub fn run(command: &Command, out: &mut impl Write) -> Result<()> {
+  match command {
+    Command::Convert { color, to } => convert(color, *to, out),
+    Command::Generate { harmony, color, space, size, format } => {
+      let colors = generate_palette_with_harmony(color.clone(), *harmony, *space, &HarmonyParams::default().with_count(*size));
+      let doc = PaletteDocument::new("", colors).with_harmony(*harmony, color.clone());
+      write!(out, "{}", export_palette(&doc, *format)?)?;
+      Ok(())
+    },
+    Command::Contrast { a, b, metric } => {
+      writeln!(out, "{}", metric.describe(a, b).trim())?;
+      Ok(())
+    },
+    Command::Check { palette, metric, threshold } => {
+      let doc = PaletteStore::new().resolve(palette)?;
+      check(&doc.colors, *metric, threshold.unwrap_or(metric.default_threshold()), out)
+    },
+    Command::Export { palette, format, roles } => {
+      let doc = PaletteStore::new().resolve(palette)?;
+      let s = if *roles {export_roles(&doc, *format)?} else {export_palette(&doc, *format)?};
+      write!(out, "{}", s)?;
+      Ok(())
+    },
+    Command::Generators => {
+      for generator in registry() {
+        let params: Vec<&str> = generator.params().iter().map(|p| p.name()).collect();
+        writeln!(out, "{:<21}{} [{}]", generator.name(), generator.description(), params.join(", "))?;
+      }
+      Ok(())
+    },
+  }
+}
+
+/// Prints the near-duplicate slots for normal vision and every simulated color vision deficiency.
+fn check(colors: &Colors, metric: DeltaE, threshold: f64, out: &mut impl Write) -> Result<()> {
+  let visions = std::iter::once(("normal", colors.clone())).chain(Cvd::ALL.map(|cvd| (cvd.name(), colors.simulate_cvd(cvd))));
+  for (vision, colors) in visions {
+    let pairs = colors.near_duplicates(metric, threshold);
+    if pairs.is_empty() {
+      writeln!(out, "{:<14}ok", vision)?;
+    }
+    for (x, y, distance) in pairs {
+      writeln!(out, "{:<14}{} ~ {} ({} {:.2})", vision, x, y, metric.name(), distance)?;
+    }
+  }
+  Ok(())
+}
+
+fn format_model(color: &ColorRGB, model: ColorModel) -> String {
+  match model {
+    ColorModel::Hex => color.to_hex(),
+    ColorModel::Rgb => format!("{}, {}, {}", color.r, color.g, color.b),
+    ColorModel::Hsv => {
+      let (h, s, v) = color.rgb_to_hsv();
+      format!("{:.0}, {:.3}, {:.3}", h, s, v)
+    },
+    ColorModel::Hsl => {
+      let (h, s, l) = color.rgb_to_hsl();
+      format!("{:.0}, {:.3}, {:.3}", h, s, l)
+    },
+    ColorModel::Xyz => {
+      let (x, y, z) = color.rgb_to_xyz();
+      format!("{:.4}, {:.4}, {:.4}", x, y, z)
+    },
+    ColorModel::Lab => {
+      let (l, a, b) = color.rgb_to_lab();
+      format!("{:.2}, {:.2}, {:.2}", l, a, b)
+    },
+    ColorModel::Lch => {
+      let (l, c, h) = color.rgb_to_lch();
+      format!("{:.2}, {:.2}, {:.2}", l, c, h)
+    },
+    ColorModel::Oklab => {
+      let (l, a, b) = color.rgb_to_oklab();
+      format!("{:.4}, {:.4}, {:.4}", l, a, b)
+    },
+    ColorModel::Oklch => {
+      let (l, c, h) = color.rgb_to_oklch();
+      format!("{:.4}, {:.4}, {:.2}", l, c, h)
+    },
+  }
+}
+
+fn convert(color: &ColorRGB, to: Option<ColorModel>, out: &mut impl Write) -> Result<()> {
+  match to {
+    Some(model) => writeln!(out, "{}", format_model(color, model))?,
+    None => {
+      for model in ColorModel::value_variants() {
+        let label = model.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default();
+        writeln!(out, "{}: {}", label, format_model(color, *model))?;
+      }
+    },
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::{colors::{generators::Harmony, perceptual::ColorSpace, ContrastMetric}, export::ExportFormat};
+
+  fn run_to_string(command: Command) -> Result<String> {
+    let mut out = Vec::new();
+    run(&command, &mut out)?;
+    Ok(String::from_utf8(out)?)
+  }
+
+  #[test]
+  fn test_convert() -> Result<()> {
+    let color = ColorRGB::new(0, 238, 236);
+    let s = run_to_string(Command::Convert { color: color.clone(), to: None })?;
+    assert_eq!(s, concat!(
+      "hex: #00EEEC\nrgb: 0, 238, 236\nhsv: 179, 1.000, 0.933\nhsl: 179, 1.000, 0.467\n",
+      "xyz: 0.4571, 0.6720, 0.8990\nlab: 85.60, -46.22, -12.45\nlch: 85.60, 47.87, 195.07\n",
+      "oklab: 0.8587, -0.1427, -0.0347\noklch: 0.8587, 0.1469, 193.68\n",
+    ));
+    let s = run_to_string(Command::Convert { color, to: Some(ColorModel::Rgb) })?;
+    assert_eq!(s, "0, 238, 236\n");
+    Ok(())
+  }
+
+  #[test]
+  fn test_generate() -> Result<()> {
+    let color = ColorRGB::new(0, 238, 236);
+    let triadic = Harmony::TRIADIC;
+    let s = run_to_string(Command::Generate { harmony: triadic, color: color.clone(), space: ColorSpace::Hsv, size: 5, format: ExportFormat::Json })?;
+    let doc = PaletteDocument::parse(&s, crate::colors::schema::PaletteFormat::Json)?;
+    assert_eq!(doc.harmony, Some(triadic));
+    assert_eq!(doc.colors.by_name("color_a"), Some(&ColorRGB::new(0, 238, 236)));
+    let s = run_to_string(Command::Generate { harmony: triadic, color, space: ColorSpace::Hsv, size: 8, format: ExportFormat::Hex })?;
+    assert_eq!(s.lines().count(), 8);
+    assert!(s.lines().last().unwrap().starts_with("color_f"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_generators() -> Result<()> {
+    let s = run_to_string(Command::Generators)?;
+    assert_eq!(s.lines().count(), registry().len());
+    assert!(s.starts_with("monochromatic        Shades"));
+    assert!(s.contains("analogous            Neighboring hues a spread apart, with a lighter and a darker shade [spread, lightness, saturation, count]\n"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_check() -> Result<()> {
+    let colors = Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(200, 60, 60),
+      ColorRGB::new(104, 88, 56),
+      ColorRGB::new(72, 220, 3),
+    ]);
+    let mut out = Vec::new();
+    check(&colors, DeltaE::Ciede2000, 5.0, &mut out)?;
+    let s = String::from_utf8(out)?;
+    assert!(s.starts_with("normal        ok\n"));
+    assert!(s.contains("protanopia    color_b ~ color_c (ΔE00 "));
+    Ok(())
+  }
+
+  #[test]
+  fn test_contrast() -> Result<()> {
+    let (black, white) = (ColorRGB::new(0, 0, 0), ColorRGB::new(255, 255, 255));
+    let s = run_to_string(Command::Contrast { a: black.clone(), b: white.clone(), metric: ContrastMetric::Wcag })?;
+    assert_eq!(s, "21.00:1 AAA\n");
+    let s = run_to_string(Command::Contrast { a: white, b: black, metric: ContrastMetric::Apca })?;
+    assert_eq!(s, "Lc-107.9 Body\n");
+    Ok(())
+  }
+}
@@ -9,12 +9,12 @@ use ui::hsv;
 pub mod drect;
 use drect::DRect;
 
-use std::{collections::HashMap, time::Duration};
+use std::{collections::{BTreeSet, HashMap}, str::FromStr, time::Duration};
 
 use ratatui::widgets::canvas::Shape;
 
-use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use color_eyre::eyre::{eyre, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{prelude::*, widgets::*};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
@@ -22,17 +22,16 @@ use tokio::sync::mpsc::UnboundedSender;
 use super::{Component, Frame};
 use crate::{
   action::Action,
-  config::{Config, KeyBindings},
-  colors::{Colors, ColorRGB, get_contrast, generators::{self, Harmony}},
+  config::{Config, KeyBindings, PaletteConfig},
+  mode::Mode,
+  colors::{Colors, ColorRGB, ContrastMetric, ansi::ColorDepth, Cvd, TextSize, WcagLevel, wcag_threshold, generators::{self, Harmony, HarmonyParams, Param}, names::{self, NamedColor}, perceptual::{ColorSpace, DeltaE, OKLCH_MAX_CHROMA}, roles::{Role, RoleSource, Roles}, schema::PaletteDocument},
+  palettes::PaletteStore,
 };
 
 use tui_input::{backend::crossterm::EventHandler, Input};
 
-use std::sync::OnceLock;
-use regex::Regex;
 
 
-pub static RGB_REGEX: OnceLock<Regex> = OnceLock::new();
 
 
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
@@ -44,21 +43,21 @@ pub enum HSVMode {
 }
 
 
+/// Color model of the three sliders in the HSV popup.
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
-pub enum InputMode {
+pub enum SliderModel {
   #[default]
-  HEX,
-  RGB,
+  HSV,
+  HSL,
+  OKLCH,
 }
 
+
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
-pub enum InputSelector {
+pub enum InputMode {
   #[default]
-  Background,
-  A,
-  B,
-  C,
-  Highlight,
+  HEX,
+  RGB,
 }
 
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
@@ -69,6 +68,12 @@ pub enum DisplayMode {
   Shades,
   HSV,
   Palette,
+  SavePrompt,
+  Load,
+  RenamePrompt,
+  ContrastMatrix,
+  NamedColors,
+  Roles,
 }
 
 #[derive(Default)]
@@ -81,16 +86,32 @@ pub struct Home {
   
   display_mode: DisplayMode,
   input_mode: InputMode,
-  input_selector: InputSelector,
+  selected: usize, // index of the selected slot, 0 is the background
   hsv_mode: HSVMode,
+  slider_model: SliderModel,
   hsv_color: ColorRGB,
   selected_harmony: Harmony,
+  harmony_space: ColorSpace,
+  harmony_params: HarmonyParams,
+  palette_param: usize, // index into the parameters of the selected generator
+  contrast_metric: ContrastMetric,
+  contrast_target: WcagLevel,
+  status: String, // message in the bottom border of the main view
+  cvd: Option<Cvd>, // simulated color vision deficiency of the main view
+  preview_depth: ColorDepth, // the whole UI is drawn as a terminal of this depth shows it
+  color_depth: ColorDepth, // what the terminal can show, detected or from --color-depth
+  live_apply: bool, // the hosting terminal is themed with the palette
+  applied: Option<Colors>, // colors last sent to the terminal while live applying
   //selected_color: ColorRGB, // take this out, oh just worked nice..
-  color_history: Vec<Colors>,
-  redo_history: Vec<Colors>,
+  palette_origin: Option<(Harmony, ColorRGB)>, // harmony and base of the current colors, if generated
+  roles: Roles, // semantic roles that were set, saved with the palette
+  locked: BTreeSet<String>, // names of the slots palette generation keeps
+  color_history: Vec<(Colors, Roles)>,
+  redo_history: Vec<(Colors, Roles)>,
 
   inputstr: String,
   inputerr: String,
+  typed_text: bool, // the last key went into a color prompt as text, so its hotkey is ignored
   anim_querycursor: Animation<String>,
 
   marker_type: Marker,
@@ -101,17 +122,25 @@ pub struct Home {
 
   shade_list: StatefulList<(StyledLine, String)>, // string is shade
 
+  palette_store: PaletteStore,
+  palette_list: StatefulList<PaletteDocument>,
+  terminal_palette: Option<PaletteDocument>, // colors read from the terminal, listed first in the palette list
+  named_list: StatefulList<NamedColor>, // matches of the named color search
+  role_list: StatefulList<Role>,
+  rename_from: String,
+  delete_armed: Option<String>, // saved palette the next DeletePalette deletes, armed by the first press
+
 }
 
 impl Home {
   pub fn new() -> Self {
     let mut this = Self::default();
-    let colors: Colors = Colors { 
-      background: ColorRGB::new(32,32,32),
-      color_a: ColorRGB::new(255,255,255),
-      color_b: ColorRGB::new(144,72,93),
-      color_c: ColorRGB::new(26,97,127),
-      highlight: ColorRGB::new(72,220,3) };
+    let colors: Colors = Colors::from_colors(vec![
+      ColorRGB::new(32,32,32),
+      ColorRGB::new(255,255,255),
+      ColorRGB::new(144,72,93),
+      ColorRGB::new(26,97,127),
+      ColorRGB::new(72,220,3)]);
     this.colors = colors;
     this.anim_querycursor = Animation::with_items(vec!["".to_string()," ".to_string()]);
     this.anim_rect = Animation::with_items(vec![
@@ -122,62 +151,65 @@ impl Home {
     this._anim_cube = DCube::new(70.0, 30.0, 40.0, 40.0);
     this.shade_list = this.create_shade_list();
     this.rect_spins = false;
+    this.palette_store = PaletteStore::new();
+    this.role_list = StatefulList::with_items(Role::ALL.to_vec());
+    this.role_list.state.select(Some(0));
     this
   }
 
+  /// Applies the startup state from the `palette` section of the config.
+  pub fn with_palette_config(mut self, palette: &PaletteConfig) -> Result<Self> {
+    self.colors = palette.colors().map_err(|e| eyre!("invalid palette.colors: {}", e))?;
+    self.shade_list = self.create_shade_list();
+    self.selected_harmony = palette.harmony.unwrap_or(self.selected_harmony);
+    self.harmony_space = palette.space.unwrap_or(self.harmony_space);
+    self.marker_type = palette.marker.unwrap_or(self.marker_type);
+    self.rect_spins = palette.spin.unwrap_or(self.rect_spins);
+    Ok(self)
+  }
 
-  pub fn next_color(&mut self) {
-    match self.input_selector {
-      InputSelector::Background => {self.input_selector = InputSelector::A},
-      InputSelector::A => {self.input_selector = InputSelector::B},
-      InputSelector::B => {self.input_selector = InputSelector::C},
-      InputSelector::C => {self.input_selector = InputSelector::Highlight},
-      InputSelector::Highlight => {self.input_selector = InputSelector::Background},
+  pub fn with_color_depth(mut self, color_depth: ColorDepth) -> Self {
+    self.color_depth = color_depth;
+    self
+  }
+
+  pub fn with_terminal_palette(mut self, palette: PaletteDocument) -> Self {
+    self.terminal_palette = Some(palette);
+    self
+  }
+
+  /// Starts with the given palette instead of the default colors, as the root of the undo history.
+  pub fn with_palette(mut self, palette: PaletteDocument) -> Self {
+    self.colors = palette.colors;
+    self.roles = palette.roles;
+    self.selected = 0;
+    if let (Some(harmony), Some(base)) = (palette.harmony, palette.base) {
+      self.selected_harmony = harmony;
+      self.palette_origin = Some((harmony, base));
     }
+    self.color_history.clear();
+    self.redo_history.clear();
+    self.shade_list = self.create_shade_list();
+    self
+  }
+
+
+  pub fn next_color(&mut self) {
+    self.selected = (self.selected + 1) % self.colors.len();
     self.shade_list = self.create_shade_list();  
   }
 
   pub fn previous_color(&mut self) {
-    match self.input_selector {
-      InputSelector::Background => {self.input_selector = InputSelector::Highlight},
-      InputSelector::A => {self.input_selector = InputSelector::Background},
-      InputSelector::B => {self.input_selector = InputSelector::A},
-      InputSelector::C => {self.input_selector = InputSelector::B},
-      InputSelector::Highlight => {self.input_selector = InputSelector::C},
-    }
+    self.selected = (self.selected + self.colors.len() - 1) % self.colors.len();
     self.shade_list = self.create_shade_list(); 
   }
 
-/*   pub fn select_color_by_mode(&mut self) {
-    match self.input_selector {
-      InputSelector::Background => {self.selected_color = self.colors.background.clone();},
-      InputSelector::A => {self.selected_color = self.colors.color_a.clone();},
-      InputSelector::B => {self.selected_color = self.colors.color_b.clone();},
-      InputSelector::C => {self.selected_color = self.colors.color_c.clone();},
-      InputSelector::Highlight => {self.selected_color = self.colors.highlight.clone();},
-    }
-  } */
-
   pub fn get_color_by_mode(&self) -> ColorRGB {
-    match self.input_selector {
-      InputSelector::Background => {self.colors.background.clone()},
-      InputSelector::A => {self.colors.color_a.clone()},
-      InputSelector::B => {self.colors.color_b.clone()},
-      InputSelector::C => {self.colors.color_c.clone()},
-      InputSelector::Highlight => {self.colors.highlight.clone()},
-    }  
+    self.colors.color(self.selected).clone()
   }
 
   pub fn make_colors_by_mode(&mut self, color:ColorRGB) -> Colors {
-    let mut colors = self.colors.clone();
-    match self.input_selector {
-      InputSelector::Background => {colors.background= color;},
-      InputSelector::A => {colors.color_a = color;},
-      InputSelector::B => {colors.color_b = color;},
-      InputSelector::C => {colors.color_c = color;},
-      InputSelector::Highlight => {colors.highlight = color;},
-    }
-    colors
+    self.colors.with_color(self.selected, color)
   }
 
   pub fn invert_color(&mut self) {
@@ -188,67 +220,76 @@ impl Home {
   }
 
   pub fn invert_all(&mut self) {
-    let mut colors = self.colors.clone();
-    colors.background = ColorRGB::from_color(colors.background.flip_rgb()).unwrap();
-    colors.color_a = ColorRGB::from_color(colors.color_a.flip_rgb()).unwrap();
-    colors.color_b = ColorRGB::from_color(colors.color_b.flip_rgb()).unwrap();
-    colors.color_c = ColorRGB::from_color(colors.color_c.flip_rgb()).unwrap();
-    colors.highlight = ColorRGB::from_color(colors.highlight.flip_rgb()).unwrap();
+    let colors = self.colors.map(|color| ColorRGB::from_color(color.flip_rgb()).unwrap());
     self.change_color(colors);
   }
 
+  /// Names the slot pairs that are hard to tell apart in the shown (possibly simulated) colors.
+  fn near_duplicate_warning(&self) -> String {
+    let metric = DeltaE::Ciede2000;
+    let pairs = self.colors.near_duplicates(metric, metric.default_threshold());
+    if pairs.is_empty() {
+      return String::new();
+    }
+    let pairs: Vec<String> = pairs.iter().map(|(x, y, distance)| format!("{} ~ {} ({} {:.1})", x, y, metric.name(), distance)).collect();
+    format!(" Hard to tell apart: {} ", pairs.join(", "))
+  }
+
   pub fn create_styled_paragraph(&self) -> Paragraph {
+    const WORDS: [&str; 8] = ["Lorem", "ipsum", "doloret", "volce", "sit", "amet", "consect", "adipisc"];
+    let background = self.colors.background().clone();
+    let mut lines = vec![create_paragraph_line("Background".to_string(), background.clone(), background.clone(), self.contrast_metric)];
+    for (color, word) in self.colors.foregrounds().zip(WORDS.iter().cycle()) {
+      lines.push(create_paragraph_line(word.to_string(), color.clone(), background.clone(), self.contrast_metric));
+    }
     Paragraph::new(
-      vec![
-        create_paragraph_line("Background".to_string(), self.colors.background.clone(), self.colors.background.clone()),
-        create_paragraph_line("Lorem".to_string(), self.colors.color_a.clone(), self.colors.background.clone()),
-        create_paragraph_line("ipsum".to_string(), self.colors.color_b.clone(), self.colors.background.clone()),
-        create_paragraph_line("doloret".to_string(), self.colors.color_c.clone(), self.colors.background.clone()),
-        create_paragraph_line("volce".to_string(), self.colors.highlight.clone(), self.colors.background.clone()),
-      ]
-
+      lines
     ).block(
       Block::new()
+      .title(block::Title::from(format!(" Contrast: {} | Fix target: {} ", self.contrast_metric.name(), self.contrast_target.name())).alignment(Alignment::Right))
+      .title(block::Title::from(self.view_title()).alignment(Alignment::Left))
+      .title(block::Title::from(if self.status.is_empty() {String::new()} else {format!(" {} ", self.status)}).alignment(Alignment::Left).position(block::Position::Bottom))
+      .title(block::Title::from(self.near_duplicate_warning()).alignment(Alignment::Right).position(block::Position::Bottom))
       .border_style(
-        Style::new().fg(self.colors.background.flip_rgb()))
+        Style::new().fg(self.colors.background().flip_rgb()))
       .borders(Borders::ALL)
-      .bg(self.colors.background.color))
+      .bg(self.colors.background().color))
 
   }
 
-  pub fn create_input_box(&self, selection: InputSelector, color: ColorRGB, text:String) -> Paragraph {
-    let selected = if self.input_selector == selection {true} else {false};
+  pub fn create_input_box(&self, selection: usize, color: ColorRGB, text:String) -> Paragraph<'_> {
+    let selected = self.selected == selection;
     let fddhus = text.clone();
 
     Paragraph::new(
       vec![
-        create_input_paragraph_line(self.input_mode.clone(), fddhus , color.clone(), self.colors.background.clone()),
+        create_input_paragraph_line(self.input_mode, fddhus , color.clone(), self.colors.background().clone()),
       ]
     ).block(
       Block::new()
       .border_style(
-        Style::new().fg(if selected{self.colors.highlight.color.clone()} else {self.colors.background.flip_rgb().clone()} ))
+        Style::new().fg(if selected{self.colors.accent().color} else {self.colors.background().flip_rgb()} ))
       .borders(Borders::ALL)
-      .bg(self.colors.background.color.clone()))
+      .bg(self.colors.background().color))
 
   }
 
   pub fn create_shade_list(&mut self) -> StatefulList<(StyledLine, String)> {
     let color = self.get_color_by_mode();
-    StatefulList::with_items(create_styled_shade_lines(color, self.colors.background.clone()))
+    StatefulList::with_items(create_styled_shade_lines(color, self.colors.background().clone(), self.contrast_metric))
   }
 
   pub fn popup_shades(&mut self) -> impl Widget + '_ {
     let color = self.get_color_by_mode();
-    let lines = create_shade_lines(color.clone(), self.colors.background.clone());
+    let lines = create_shade_lines(color.clone(), self.colors.background().clone());
     let titlestr = format!(" Shades for {} ", color.color.to_string());
 
     let shadebox = Paragraph::new(lines)
-    .set_style(Style::new().fg(self.colors.background.flip_rgb()))
+    .set_style(Style::new().fg(self.colors.background().flip_rgb()))
     .block(Block::default()
-    .bg(self.colors.background.color)
+    .bg(self.colors.background().color)
     .borders(Borders::ALL)
-    .border_style(Style::new().fg(self.colors.background.flip_rgb()))
+    .border_style(Style::new().fg(self.colors.background().flip_rgb()))
 
     .title(titlestr));
     shadebox
@@ -257,10 +298,10 @@ impl Home {
   pub fn popup_input_prompt(&mut self) -> impl Widget + '_ {
     
     let sel_col = self.get_color_by_mode();
-    let isbkg = if self.input_selector == InputSelector::Background {true} else {false};
+    let isbkg = self.selected == 0;
 
-    let mut titlestr = "[ Insert RGB (r,g,b) ]";
-    if self.input_mode == InputMode::HEX { titlestr = "[ Insert Hex # ]";};
+    let mut titlestr = "[ Insert RGB (r,g,b) or CSS color ]";
+    if self.input_mode == InputMode::HEX { titlestr = "[ Insert Hex # or CSS color ]";};
 
     let querycursor = self.anim_querycursor.state.selected().unwrap();
     let querycursor = self.anim_querycursor.keyframes[querycursor].clone();
@@ -268,20 +309,20 @@ impl Home {
 
     let mut querytext: Vec<Line> = vec![];
     let queryline =   Line::from(vec![
-      Span::styled(format!("  {}", self.inputstr), Style::default().fg(self.colors.background.flip_rgb())) , 
-      Span::styled(querycursor, Style::default().bg(self.colors.background.flip_rgb()))
+      Span::styled(format!("  {}", self.inputstr), Style::default().fg(self.colors.background().flip_rgb())) , 
+      Span::styled(querycursor, Style::default().bg(self.colors.background().flip_rgb()))
       ]);
     querytext.push(Line::from(""));
     //queryline.patch_style(self.apptheme.selected_ip_bg);
     querytext.push(queryline);
     let mut queryerror =   Line::from(format!(" -> {}", self.inputerr));
-    queryerror.patch_style(Style::new().fg(self.colors.background.flip_rgb()));
+    queryerror.patch_style(Style::new().fg(self.colors.background().flip_rgb()));
     querytext.push(queryerror);
 
     let querybox = Paragraph::new(querytext)
-    .set_style(Style::new().fg(self.colors.background.flip_rgb()))
+    .set_style(Style::new().fg(self.colors.background().flip_rgb()))
     .block(Block::default()
-    .bg(self.colors.background.color)
+    .bg(self.colors.background().color)
     .borders(Borders::ALL)
     .border_style(Style::new().fg(
       if isbkg {sel_col.flip_rgb()} else {sel_col.color}
@@ -291,96 +332,295 @@ impl Home {
 
   }
 
-  pub fn popup_palette(&mut self)  -> impl Widget + '_ {
-    let color = self.get_color_by_mode();
-    let colors_mono = generators::monochromatic::generate_monochromatic(color.clone());
-    let colors_analog = generators::analogous::generate_analogous(color.clone());
-    let colors_comp = generators::complementary::generate_complementary(color.clone(), 5);
-    let colors_spl_comp = generators::split_complementary::generate_split_complementary(color.clone());
-    let colors_tri = generators::triadic::generate_triadic(color.clone());
-    let colors_tet = generators::tetradic::generate_tetradic(color.clone());
+  pub fn popup_palette(&mut self, f: &mut Frame<'_>, area: Rect) {
+    let fg = self.colors.background().flip_rgb();
+    let space = self.harmony_space;
+    let params = self.harmony_params;
+    let harmonies: Vec<Harmony> = Harmony::all().collect();
+    let palettes: Vec<Colors> = harmonies.iter().map(|harmony| self.generate_palette(*harmony)).collect();
+    let size = palettes[0].len();
+
+    let titlestr = format!(" Palettes ({}) ", space.name());
+    let block = Block::default()
+      .bg(self.colors.background().color)
+      .title(block::Title::from(titlestr).alignment(Alignment::Center))
+      .title(block::Title::from(" Up/Down: parameter | Shift-Left/Right: change ").alignment(Alignment::Left).position(block::Position::Bottom))
+      .borders(Borders::ALL)
+      .border_style(Style::new().fg(fg));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
     // Palette should be pickable either as a random palette or based on selected color
     // https://www.thecolorapi.com/docs
-    let lines = vec![
-      Line::from(vec![  Span::styled(" Mon ", Style::new().fg(if self.selected_harmony == Harmony::Monochromatic {self.colors.highlight.color} else {self.colors.background.flip_rgb()} )),
-                        Span::styled("     ", Style::new()),
-                        Span::styled(" Ana ", Style::new().fg(if self.selected_harmony == Harmony::Analogous {self.colors.highlight.color} else {self.colors.background.flip_rgb()} )),
-                        Span::styled("     ", Style::new()),
-                        Span::styled(" Com ", Style::new().fg(if self.selected_harmony == Harmony::Complementary {self.colors.highlight.color} else {self.colors.background.flip_rgb()} )),
-                        Span::styled("     ", Style::new()),
-                        Span::styled(" Spl ", Style::new().fg(if self.selected_harmony == Harmony::SplitComplementary {self.colors.highlight.color} else {self.colors.background.flip_rgb()} )),
-                        Span::styled("     ", Style::new()),
-                        Span::styled(" Tri ", Style::new().fg(if self.selected_harmony == Harmony::Triadic {self.colors.highlight.color} else {self.colors.background.flip_rgb()} )),
-                        Span::styled("     ", Style::new()),
-                        Span::styled(" Tet ", Style::new().fg(if self.selected_harmony == Harmony::Tetradic {self.colors.highlight.color} else {self.colors.background.flip_rgb()} )),
-      ]),
+    let mut header = Line::from(" ");
+    for &harmony in harmonies.iter() {
+      let short: String = harmony.name().chars().take(3).collect();
+      let fg = if self.selected_harmony == harmony {self.colors.accent().color} else {fg};
+      header.spans.push(Span::styled(format!(" {}{} ", short[..1].to_uppercase(), &short[1..]), Style::new().fg(fg)));
+      header.spans.push(Span::styled("     ", Style::new()));
+    }
+    header.spans.pop();
+    // one row per slot, one column per harmony, the selected slot marked
+    let mut rows = vec![];
+    for slot in 0..size {
+      let mut line = Line::from(Span::styled(if slot == self.selected {">"} else {" "}, Style::new().fg(self.colors.accent().color)));
+      for colors in palettes.iter() {
+        line.spans.push(Span::styled("     ", Style::new().bg(colors.color(slot).color)));
+        line.spans.push(Span::styled("     ", Style::new()));
+      }
+      line.spans.pop();
+      rows.push(line);
+    }
 
+    // the selected generator and its parameters, the selected one in the accent color
+    let generator = self.selected_harmony.generator();
+    let mut text = vec![Line::default(), Line::styled(format!(" {}", generator.description()), Style::new().fg(fg))];
+    let selected = self.selected_palette_param();
+    let mut line = Line::default();
+    for param in generator.params() {
+      let fg = if Some(*param) == selected {self.colors.accent().color} else {fg};
+      line.spans.push(Span::styled(format!(" {} ", param.describe(&params)), Style::new().fg(fg)));
+    }
+    text.push(line);
+    if !self.locked.is_empty() {
+      let locked: Vec<&str> = self.locked_slots().into_iter().map(|idx| self.colors.name(idx)).collect();
+      text.push(Line::styled(format!(" Locked: {}", locked.join(", ")), Style::new().fg(fg)));
+    }
+    let dropped = self.dropped_slots();
+    if !dropped.is_empty() {
+      text.push(Line::styled(format!(" Applying drops {}", dropped.join(", ")), Style::new().fg(fg)));
+    }
 
-      Line::from(vec![  Span::styled("     ", Style::new().bg(colors_mono.background.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_analog.background.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_comp.background.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_spl_comp.background.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tri.background.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tet.background.color)),
-      ]),
-      Line::from(vec![  Span::styled("     ", Style::new().bg(colors_mono.color_a.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_analog.color_a.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_comp.color_a.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_spl_comp.color_a.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tri.color_a.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tet.color_a.color)),
-      ]),
-      Line::from(vec![  Span::styled("     ", Style::new().bg(colors_mono.color_b.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_analog.color_b.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_comp.color_b.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_spl_comp.color_b.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tri.color_b.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tet.color_b.color)),
-      ]),
-      Line::from(vec![  Span::styled("     ", Style::new().bg(colors_mono.color_c.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_analog.color_c.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_comp.color_c.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_spl_comp.color_c.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tri.color_c.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tet.color_c.color)),
-      ]),
-      Line::from(vec![  Span::styled("     ", Style::new().bg(colors_mono.highlight.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_analog.highlight.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_comp.highlight.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_spl_comp.highlight.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tri.highlight.color)),
-                        Span::styled("     ", Style::new()),
-                        Span::styled("     ", Style::new().bg(colors_tet.highlight.color)),
+    // the grid is clipped and its rows scrolled so the selected slot is shown, only the text below it wraps
+    let width = inner.width.max(1) as usize;
+    let text_height: usize = text.iter().map(|line| line.width().div_ceil(width).max(1)).sum();
+    let shown = rows.len().min((inner.height as usize).saturating_sub(text_height + 1).max(1));
+    let first = self.selected.min(size - 1).saturating_sub(shown - 1);
+    let lines: Vec<Line> = std::iter::once(header).chain(rows.into_iter().skip(first).take(shown)).collect();
+    let grid_height = lines.len() as u16;
+    let layout = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Length(grid_height), Constraint::Min(0)])
+      .split(inner);
+    f.render_widget(Paragraph::new(lines), layout[0]);
+    f.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), layout[1]);
+  }
+
+  pub fn popup_name_prompt(&mut self) -> impl Widget + '_ {
+    let titlestr = if self.display_mode == DisplayMode::RenamePrompt {
+      format!("[ Rename {} ]", self.rename_from)
+    } else {
+      "[ Save Palette As ]".to_string()
+    };
+
+    let querycursor = self.anim_querycursor.state.selected().unwrap_or(0);
+    let querycursor = self.anim_querycursor.keyframes[querycursor].clone();
+
+    let querytext: Vec<Line> = vec![
+      Line::from(""),
+      Line::from(vec![
+        Span::styled(format!("  {}", self.inputstr), Style::default().fg(self.colors.background().flip_rgb())),
+        Span::styled(querycursor, Style::default().bg(self.colors.background().flip_rgb())),
       ]),
+      Line::styled(format!(" -> {}", self.inputerr), Style::new().fg(self.colors.background().flip_rgb())),
+    ];
+
+    Paragraph::new(querytext)
+    .block(Block::default()
+    .bg(self.colors.background().color)
+    .borders(Borders::ALL)
+    .border_style(Style::new().fg(self.colors.accent().color))
+    .title(titlestr).title_alignment(Alignment::Center)).alignment(Alignment::Left)
+  }
+
+  pub fn popup_load(&mut self, f: &mut Frame<'_>, area: Rect) {
+    let fg = self.colors.background().flip_rgb();
+    let items: Vec<ListItem> = self.palette_list
+      .items
+      .iter()
+      .enumerate()
+      .map(|(i, p)| {
+        let name_style = if self.is_terminal_entry(i) {Style::new().fg(fg).italic()} else {Style::new().fg(fg)};
+        let mut line = Line::from(Span::styled(format!(" {} ", pad_to_length(p.name.clone(), 24)), name_style));
+        for color in p.colors.colors() {
+          line.spans.push(Span::styled("    ", Style::new().bg(color.color)));
+          line.spans.push(Span::styled(" ", Style::new()));
+        }
+        ListItem::new(line)
+      })
+      .collect();
+
+    let status = if self.inputerr.is_empty() {
+      " Enter: load | Del: delete | Ctrl-r: rename ".to_string()
+    } else {
+      format!(" {} ", self.inputerr)
+    };
+
+    let list = List::new(items)
+      .block(Block::default()
+        .bg(self.colors.background().color)
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(fg))
+        .title(block::Title::from(" Load Palette ").alignment(Alignment::Center))
+        .title(block::Title::from(status).alignment(Alignment::Left).position(block::Position::Bottom))
+      )
+      .highlight_style(Style::new().fg(self.colors.accent().color))
+      .highlight_symbol(">> ");
+
+    f.render_stateful_widget(list, area, &mut self.palette_list.state);
+  }
+
+  /// Search field over the named colors with a swatch per match, and the name nearest to the selected color.
+  pub fn popup_named_colors(&mut self, f: &mut Frame<'_>, area: Rect) {
+    let fg = self.colors.background().flip_rgb();
+    let querycursor = self.anim_querycursor.state.selected().unwrap_or(0);
+    let querycursor = self.anim_querycursor.keyframes[querycursor].clone();
+
+    let (nearest, delta) = names::nearest(&self.get_color_by_mode());
+    let status = if self.inputerr.is_empty() {
+      format!(" Nearest: {} ({}) {} {:.2} ", nearest.name, nearest.source.name(), DeltaE::Ciede2000.name(), delta)
+    } else {
+      format!(" {} ", self.inputerr)
+    };
+    let block = Block::default()
+      .bg(self.colors.background().color)
+      .borders(Borders::ALL)
+      .border_style(Style::new().fg(fg))
+      .title(block::Title::from(" Named Colors ").alignment(Alignment::Center))
+      .title(block::Title::from(status).alignment(Alignment::Left).position(block::Position::Bottom));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let layout = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Length(2), Constraint::Min(0)])
+      .split(inner);
+
+    let hint = if self.inputstr.is_empty() {" type to search, nearest names first"} else {""};
+    f.render_widget(Paragraph::new(Line::from(vec![
+      Span::styled(format!("  {}", self.inputstr), Style::new().fg(fg)),
+      Span::styled(querycursor, Style::new().bg(fg)),
+      Span::styled(hint, Style::new().fg(fg).add_modifier(Modifier::DIM)),
+    ])), layout[0]);
+
+    let items: Vec<ListItem> = self.named_list
+      .items
+      .iter()
+      .map(|named| {
+        ListItem::new(Line::from(vec![
+          Span::styled("    ", Style::new().bg(named.color.color)),
+          Span::styled(format!(" {} ", pad_to_length(named.name.to_string(), 24)), Style::new().fg(fg)),
+          Span::styled(format!("{} ", pad_to_length(named.source.name().to_string(), 5)), Style::new().fg(fg).add_modifier(Modifier::DIM)),
+          Span::styled(named.color.to_hex(), Style::new().fg(fg)),
+        ]))
+      })
+      .collect();
+
+    let list = List::new(items)
+      .highlight_style(Style::new().fg(self.colors.accent().color))
+      .highlight_symbol(">> ");
+    f.render_stateful_widget(list, layout[1], &mut self.named_list.state);
+  }
+
+  /// Every role with its color and the slot and shade it comes from.
+  pub fn popup_roles(&mut self, f: &mut Frame<'_>, area: Rect) {
+    let fg = self.colors.background().flip_rgb();
+    let items: Vec<ListItem> = self.role_list
+      .items
+      .iter()
+      .map(|&role| {
+        let color = self.roles.color(role, &self.colors);
+        let source = self.roles.source(role, &self.colors);
+        let set = self.roles.is_set(role, &self.colors);
+        ListItem::new(Line::from(vec![
+          Span::styled(format!(" {} ", pad_to_length(role.name().to_string(), 10)), Style::new().fg(fg)),
+          Span::styled("    ", Style::new().bg(color.color)),
+          Span::styled(format!(" {} ", pad_to_length(source.to_string(), 20)), Style::new().fg(fg)),
+          Span::styled(color.to_hex(), Style::new().fg(fg)),
+          Span::styled(if set {""} else {"  default"}, Style::new().fg(fg).add_modifier(Modifier::DIM)),
+        ]))
+      })
+      .collect();
+
+    let list = List::new(items)
+      .block(Block::default()
+        .bg(self.colors.background().color)
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(fg))
+        .title(block::Title::from(" Roles ").alignment(Alignment::Center))
+        .title(block::Title::from(" Left/Right: color | Shift: shade | Enter: default ").alignment(Alignment::Left).position(block::Position::Bottom))
+      )
+      .highlight_style(Style::new().fg(self.colors.accent().color))
+      .highlight_symbol(">> ");
+    f.render_stateful_widget(list, area, &mut self.role_list.state);
+  }
+
+  /// A code listing drawn with the roles, `width` is the inner width of its block.
+  pub fn role_preview(&self, width: usize) -> Paragraph<'_> {
+    let role = |role: Role| self.roles.color(role, &self.colors).color;
+    let lines = vec![
+      Line::from(vec![Span::styled("fn ", Style::new().fg(role(Role::Accent))), Span::styled("main() {", Style::new().fg(role(Role::Fg)))]),
+      Line::styled("  // semantic roles", Style::new().fg(role(Role::Comment))),
+      Line::styled(pad_to_length("  let selected = 42;".to_string(), width), Style::new().fg(role(Role::Fg)).bg(role(Role::Selection))),
+      Line::styled("  error: it broke", Style::new().fg(role(Role::Error))),
+      Line::styled("  warning: unused", Style::new().fg(role(Role::Warning))),
+      Line::styled("  ok: 3 passed", Style::new().fg(role(Role::Success))),
+      Line::styled("}", Style::new().fg(role(Role::Fg))),
     ];
+    Paragraph::new(lines).block(Block::default()
+      .bg(role(Role::Surface))
+      .borders(Borders::ALL)
+      .border_style(Style::new().fg(role(Role::Border)))
+      .title(Span::styled(" Roles ", Style::new().fg(role(Role::Accent))))
+    )
+  }
 
-    let titlestr = " Palettes ";
-    Paragraph::new(lines).bg(self.colors.background.color).block(Block::default().title(titlestr).title_alignment(Alignment::Center).borders(Borders::ALL).border_style(Style::new().fg(self.colors.background.flip_rgb())))
+  /// Grid of every slot as text (rows) on every slot as background (columns).
+  pub fn popup_contrast_matrix(&mut self, f: &mut Frame<'_>, area: Rect) {
+    let fg = self.colors.background().flip_rgb();
+    let metric = self.contrast_metric;
+    let slots = self.colors.named_slots();
+
+    let header = Row::new(
+      std::iter::once(Cell::from(" text \\ bg")).chain(slots.iter().map(|(name, _)| Cell::from(format!(" {}", name))))
+    ).style(Style::new().fg(fg)).height(1).bottom_margin(1);
+
+    let rows: Vec<Row> = slots.iter().map(|(text_name, text)| {
+      let mut cells = vec![Cell::from(format!(" {}", text_name)).style(Style::new().fg(fg))];
+      for (_, bkg) in slots.iter() {
+        let value = metric.contrast(text, bkg);
+        let pair = Style::new().fg(text.color).bg(bkg.color);
+        let badge = if metric.passes_text(value) {
+          Span::styled(" PASS ", Style::new().fg(fg).bold())
+        } else {
+          Span::styled(" FAIL ", Style::new().fg(self.colors.background().color).bg(fg))
+        };
+        let value = match metric {
+          ContrastMetric::Wcag => format!(" {:.2}:1 ", value),
+          ContrastMetric::Apca => format!(" Lc {:.1} ", value),
+        };
+        cells.push(Cell::from(vec![
+          Line::from(Span::styled(" Lorem ipsum ", pair)),
+          Line::from(Span::styled(pad_to_length(value, 13), pair)),
+          Line::from(badge),
+        ]));
+      }
+      Row::new(cells).height(3).bottom_margin(1)
+    }).collect();
+
+    let columns = slots.len() as u32 + 1;
+    let widths = vec![Constraint::Ratio(1, columns); columns as usize];
+    let table = Table::new(rows)
+      .header(header)
+      .widths(&widths)
+      .column_spacing(1)
+      .block(Block::default()
+        .bg(self.colors.background().color)
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(fg))
+        .title(block::Title::from(format!(" Contrast Matrix ({}) ", metric.name())).alignment(Alignment::Center))
+      );
+    f.render_widget(table, area);
   }
 
   pub fn popup_hsv(&mut self, f: &mut Frame<'_>, area: Rect) {
@@ -389,7 +629,12 @@ impl Home {
     
     let hsv_mode = self.hsv_mode;
     let hsv_color = self.hsv_color.clone();
-    let _hsv = hsv_color.rgb_to_hsv();
+    let slider_model = self.slider_model;
+    let _hsv = match slider_model {
+      SliderModel::HSV => {hsv_color.rgb_to_hsv()},
+      SliderModel::HSL => {hsv_color.rgb_to_hsl()},
+      SliderModel::OKLCH => {ColorSpace::Oklch.to_polar(&hsv_color)},
+    };
     // popup need three sliders
     // H: 0   -> 360°
     // S, V : -> 0% to 100%
@@ -401,8 +646,9 @@ impl Home {
     // Color
     // Rest
        
-    let titlestr = format!(" HSV for {} ", hsv_color.color.to_string());
-    f.render_widget(Paragraph::new("").bg(self.colors.background.color).block(Block::default().title(titlestr).title_alignment(Alignment::Center).borders(Borders::ALL).border_style(Style::new().fg(self.colors.background.flip_rgb()))), area);
+    let model_name = match slider_model {SliderModel::HSV => "HSV", SliderModel::HSL => "HSL", SliderModel::OKLCH => "OKLCH"};
+    let titlestr = format!(" {} for {} ", model_name, hsv_color.color);
+    f.render_widget(Paragraph::new("").bg(self.colors.background().color).block(Block::default().title(titlestr).title_alignment(Alignment::Center).borders(Borders::ALL).border_style(Style::new().fg(self.colors.background().flip_rgb()))), area);
     
 
     let input_layout = Layout::default()
@@ -414,34 +660,43 @@ impl Home {
     let slider_layout = hsv::make_slider_layout(input_layout[0]);
 
     let border_col: Color;
-    if hsv_mode == HSVMode::H {border_col = self.colors.highlight.color;} else {border_col = self.colors.background.flip_rgb();}
-    let huecol = hsv::create_hue_column(&hsv_color);
+    if hsv_mode == HSVMode::H {border_col = self.colors.accent().color;} else {border_col = self.colors.background().flip_rgb();}
+    let huecol = if slider_model == SliderModel::OKLCH {hsv::create_oklch_hue_column(&hsv_color)} else {hsv::create_hue_column(&hsv_color)};
     f.render_widget(huecol, slider_layout[1]);
     let hueval = _hsv.0 as u64;
-    let huebar = hsv::create_bar(&self.colors.background, hueval, 360, "Hue".to_string(), border_col);
+    let huebar = hsv::create_bar(self.colors.background(), hueval, 360, "Hue".to_string(), border_col);
     f.render_widget(huebar, slider_layout[3]);
 
     let slider_layout = hsv::make_slider_layout(input_layout[1]);
 
 
     let border_col: Color;
-    if hsv_mode == HSVMode::S {border_col = self.colors.highlight.color;} else {border_col = self.colors.background.flip_rgb();}
-    let satcol = hsv::create_sat_column(&hsv_color);
+    if hsv_mode == HSVMode::S {border_col = self.colors.accent().color;} else {border_col = self.colors.background().flip_rgb();}
+    let satcol = match slider_model {
+      SliderModel::HSV => {hsv::create_sat_column(&hsv_color)},
+      SliderModel::HSL => {hsv::create_hsl_sat_column(&hsv_color)},
+      SliderModel::OKLCH => {hsv::create_chroma_column(&hsv_color)},
+    };
     f.render_widget(satcol, slider_layout[1]);
     let satval = _hsv.1*100.0;
     let satval = satval as u64;
-    let satbar = hsv::create_bar(&self.colors.background, satval, 100, "Sat".to_string(), border_col);
+    let (satmax, satlabel) = if slider_model == SliderModel::OKLCH {((OKLCH_MAX_CHROMA*100.0) as u64, "Chr")} else {(100, "Sat")};
+    let satbar = hsv::create_bar(self.colors.background(), satval, satmax, satlabel.to_string(), border_col);
     f.render_widget(satbar, slider_layout[3]);
 
     let slider_layout = hsv::make_slider_layout(input_layout[2]);
 
     let border_col: Color;
-    if hsv_mode == HSVMode::V {border_col = self.colors.highlight.color;} else {border_col = self.colors.background.flip_rgb();}
-    let valcol = hsv::create_val_column(&hsv_color);
+    if hsv_mode == HSVMode::V {border_col = self.colors.accent().color;} else {border_col = self.colors.background().flip_rgb();}
+    let valcol = match slider_model {
+      SliderModel::HSV => {hsv::create_val_column(&hsv_color)},
+      SliderModel::HSL => {hsv::create_light_column(&hsv_color)},
+      SliderModel::OKLCH => {hsv::create_oklch_light_column(&hsv_color)},
+    };
     f.render_widget(valcol, slider_layout[1]);
     let valval = _hsv.2*100.0;
     let valval = valval as u64;
-    let valbar = hsv::create_bar(&self.colors.background, valval, 100, "Val".to_string(), border_col);
+    let valbar = hsv::create_bar(self.colors.background(), valval, 100, if slider_model == SliderModel::HSV {"Val"} else {"Lgt"}.to_string(), border_col);
     f.render_widget(valbar, slider_layout[3]);
 
 
@@ -460,7 +715,7 @@ impl Home {
       .split(color_layout[1]);
     // HSL https://stackoverflow.com/questions/39118528/rgb-to-hsl-conversion
     // put color in layout[0]
-    // Paragraph::new("").bg(self.colors.background.color)
+    // Paragraph::new("").bg(self.colors.background().color)
     
     f.render_widget(Paragraph::new("").bg(self.get_color_by_mode().color), color_layout[0]);
     f.render_widget(Paragraph::new("").bg(hsv_color.color), color_layout[1]);
@@ -487,53 +742,405 @@ impl Home {
     }
   }
 
+  pub fn toggle_contrast_metric(&mut self) {
+    match self.contrast_metric {
+      ContrastMetric::Wcag => {self.contrast_metric = ContrastMetric::Apca;},
+      ContrastMetric::Apca => {self.contrast_metric = ContrastMetric::Wcag;},
+    }
+    self.shade_list = self.create_shade_list();
+  }
+
+  pub fn toggle_contrast_target(&mut self) {
+    match self.contrast_target {
+      WcagLevel::AA => {self.contrast_target = WcagLevel::AAA;},
+      WcagLevel::AAA => {self.contrast_target = WcagLevel::AA;},
+    }
+  }
+
+  /// Moves the selected color to the nearest lightness that reaches the contrast target against the background.
+  pub fn fix_contrast(&mut self) {
+    if self.display_mode != DisplayMode::Normal {return}
+    if self.selected == 0 {
+      self.status = "Select a foreground color to fix its contrast".to_string();
+      return
+    }
+    let color = self.get_color_by_mode();
+    let target = wcag_threshold(self.contrast_target, TextSize::Normal);
+    match color.with_contrast_against(self.colors.background(), target) {
+      Some(fixed) if fixed == color => {
+        self.status = format!("{} already reaches {}", color.color, self.contrast_target.name());
+      },
+      Some(fixed) => {
+        let colors = self.make_colors_by_mode(fixed.clone());
+        self.change_color(colors);
+        self.status = format!("Changed {} to {} for {}", color.color, fixed.color, self.contrast_target.name());
+      },
+      None => {
+        self.status = format!("No lightness of {} reaches {}:1 on {}", color.color, target, self.colors.background().color);
+      },
+    }
+  }
+
+  /// Cycles the simulated color vision deficiency: none, protanopia, deuteranopia, tritanopia and achromatopsia.
+  pub fn cycle_cvd(&mut self) {
+    match self.cvd {
+      None => {self.cvd = Some(Cvd::Protanopia);},
+      Some(Cvd::Protanopia) => {self.cvd = Some(Cvd::Deuteranopia);},
+      Some(Cvd::Deuteranopia) => {self.cvd = Some(Cvd::Tritanopia);},
+      Some(Cvd::Tritanopia) => {self.cvd = Some(Cvd::Achromatopsia);},
+      Some(Cvd::Achromatopsia) => {self.cvd = None;},
+    }
+  }
+
+  /// Cycles the previewed color depth: truecolor, 256 and 16 colors.
+  pub fn cycle_preview_depth(&mut self) {
+    match self.preview_depth {
+      ColorDepth::TrueColor => {self.preview_depth = ColorDepth::Ansi256;},
+      ColorDepth::Ansi256 => {self.preview_depth = ColorDepth::Ansi16;},
+      ColorDepth::Ansi16 => {self.preview_depth = ColorDepth::TrueColor;},
+    }
+  }
+
+  /// Names the terminal's color depth, the simulated color vision and the previewed color depth.
+  fn view_title(&self) -> String {
+    let view: Vec<String> = [
+      Some(format!("Terminal: {}", self.color_depth.name())),
+      self.cvd.map(|cvd| format!("Simulating {}", cvd.name())),
+      (self.preview_depth != ColorDepth::TrueColor).then(|| format!("Preview: {}", self.preview_depth.name())),
+      self.live_apply.then(|| "Live applied".to_string()),
+    ].into_iter().flatten().collect();
+    format!(" {} ", view.join(" | "))
+  }
+
+  pub fn toggle_live_apply(&mut self) -> Option<Action> {
+    self.live_apply = !self.live_apply;
+    self.applied = None;
+    if self.live_apply {self.sync_live_apply()} else {Some(Action::RestorePalette)}
+  }
+
+  /// While live applying, sends the palette to the terminal whenever it changed.
+  fn sync_live_apply(&mut self) -> Option<Action> {
+    if !self.live_apply {
+      return None;
+    }
+    let colors = self.colors.composited();
+    if self.applied.as_ref() == Some(&colors) {
+      return None;
+    }
+    self.applied = Some(colors.clone());
+    Some(Action::ApplyPalette(colors))
+  }
+
+  /// Cycles the slider model in the HSV popup and the generator space in the Palette popup.
+  pub fn switch_color_model(&mut self) {
+    match self.display_mode {
+      DisplayMode::HSV => {
+        match self.slider_model {
+          SliderModel::HSV => {self.slider_model = SliderModel::HSL;},
+          SliderModel::HSL => {self.slider_model = SliderModel::OKLCH;},
+          SliderModel::OKLCH => {self.slider_model = SliderModel::HSV;},
+        }
+      },
+      DisplayMode::Palette => {
+        match self.harmony_space {
+          ColorSpace::Hsv => {self.harmony_space = ColorSpace::Oklch;},
+          ColorSpace::Oklch => {self.harmony_space = ColorSpace::Hsv;},
+        }
+      },
+      _ => {},
+    }
+  }
+
   pub fn hsv_increase_by_mode(&mut self) {
     if self.display_mode != DisplayMode::HSV {return}
-    match self.hsv_mode {
-      HSVMode::H => {self.hsv_color = self.hsv_color.shift_hue(1.0);},//{self.hsv_color;},
-      HSVMode::S => {self.hsv_color = self.hsv_color.shift_saturation(0.01);},
-      HSVMode::V => {self.hsv_color = self.hsv_color.shift_value(0.01);},
+    match (self.hsv_mode, self.slider_model) {
+      (HSVMode::H, SliderModel::OKLCH) => {self.hsv_color = self.hsv_color.shift_oklch_hue(1.0);},
+      (HSVMode::H, _) => {self.hsv_color = self.hsv_color.shift_hue(1.0);},//{self.hsv_color;},
+      (HSVMode::S, SliderModel::HSV) => {self.hsv_color = self.hsv_color.shift_saturation(0.01);},
+      (HSVMode::V, SliderModel::HSV) => {self.hsv_color = self.hsv_color.shift_value(0.01);},
+      (HSVMode::S, SliderModel::HSL) => {self.hsv_color = self.hsv_color.shift_hsl_saturation(0.01);},
+      (HSVMode::V, SliderModel::HSL) => {self.hsv_color = self.hsv_color.shift_lightness(0.01);},
+      (HSVMode::S, SliderModel::OKLCH) => {self.hsv_color = self.hsv_color.shift_oklch_chroma(0.005);},
+      (HSVMode::V, SliderModel::OKLCH) => {self.hsv_color = self.hsv_color.shift_oklch_lightness(0.01);},
     }
     self.command_tx.clone().unwrap().send(Action::Render).expect("Error increasing HSV");
   }
 
   pub fn hsv_decrease_by_mode(&mut self) {
     if self.display_mode != DisplayMode::HSV {return}
-    match self.hsv_mode {
-      HSVMode::H => {self.hsv_color = self.hsv_color.shift_hue(-1.0);},//{self.hsv_color;},
-      HSVMode::S => {self.hsv_color = self.hsv_color.shift_saturation(-0.01);},
-      HSVMode::V => {self.hsv_color = self.hsv_color.shift_value(-0.01);},
+    match (self.hsv_mode, self.slider_model) {
+      (HSVMode::H, SliderModel::OKLCH) => {self.hsv_color = self.hsv_color.shift_oklch_hue(-1.0);},
+      (HSVMode::H, _) => {self.hsv_color = self.hsv_color.shift_hue(-1.0);},//{self.hsv_color;},
+      (HSVMode::S, SliderModel::HSV) => {self.hsv_color = self.hsv_color.shift_saturation(-0.01);},
+      (HSVMode::V, SliderModel::HSV) => {self.hsv_color = self.hsv_color.shift_value(-0.01);},
+      (HSVMode::S, SliderModel::HSL) => {self.hsv_color = self.hsv_color.shift_hsl_saturation(-0.01);},
+      (HSVMode::V, SliderModel::HSL) => {self.hsv_color = self.hsv_color.shift_lightness(-0.01);},
+      (HSVMode::S, SliderModel::OKLCH) => {self.hsv_color = self.hsv_color.shift_oklch_chroma(-0.005);},
+      (HSVMode::V, SliderModel::OKLCH) => {self.hsv_color = self.hsv_color.shift_oklch_lightness(-0.01);},
     }    
     self.command_tx.clone().unwrap().send(Action::Render).expect("Error decreasing HSV");
   }
 
-  pub fn palette_next(&mut self) {
-    if self.display_mode != DisplayMode::Palette {return}
-    match self.selected_harmony {
-      Harmony::Monochromatic => {self.selected_harmony = Harmony::Analogous},
-      Harmony::Analogous => {self.selected_harmony = Harmony::Complementary},  
-      Harmony::Complementary => {self.selected_harmony = Harmony::SplitComplementary},
-      Harmony::SplitComplementary => {self.selected_harmony = Harmony::Triadic},
-      Harmony::Triadic => {self.selected_harmony = Harmony::Tetradic},
-      Harmony::Tetradic => {self.selected_harmony = Harmony::Monochromatic},    
+  /// The parameter picked in the Palette popup, of those the selected generator reads.
+  fn selected_palette_param(&self) -> Option<Param> {
+    let params = self.selected_harmony.generator().params();
+    params.get(self.palette_param.min(params.len().saturating_sub(1))).copied()
+  }
+
+  pub fn palette_param_next(&mut self) {
+    let n = self.selected_harmony.generator().params().len().max(1);
+    self.palette_param = (self.palette_param.min(n - 1) + 1) % n;
+  }
+
+  pub fn palette_param_previous(&mut self) {
+    let n = self.selected_harmony.generator().params().len().max(1);
+    self.palette_param = (self.palette_param.min(n - 1) + n - 1) % n;
+  }
+
+  /// Steps the selected generator parameter up or down, the previews follow.
+  pub fn change_palette_param(&mut self, up: bool) {
+    if let Some(param) = self.selected_palette_param() {
+      param.step(&mut self.harmony_params, up);
     }
   }
 
-  pub fn palette_previous(&mut self) {
+  fn selected_role(&self) -> Role {
+    self.role_list.state.selected().and_then(|idx| self.role_list.items.get(idx).copied()).unwrap_or_default()
+  }
+
+  /// Points the selected role to the next (or previous) slot, keeping its shade.
+  pub fn cycle_role_slot(&mut self, next: bool) {
+    let role = self.selected_role();
+    let source = self.roles.source(role, &self.colors);
+    let n = self.colors.len();
+    let idx = self.colors.slots().iter().position(|s| s.name == source.slot).unwrap_or(0);
+    let idx = if next {(idx + 1) % n} else {(idx + n - 1) % n};
+    let mut roles = self.roles.clone();
+    roles.set(role, RoleSource::new(self.colors.name(idx), source.shade));
+    self.change_roles(roles);
+  }
+
+  /// Brightens (positive) or darkens (negative) the selected role by `step` percent.
+  pub fn shade_role(&mut self, step: i8) {
+    let role = self.selected_role();
+    let source = self.roles.source(role, &self.colors);
+    let mut roles = self.roles.clone();
+    roles.set(role, RoleSource::new(&source.slot, source.shade.saturating_add(step)));
+    self.change_roles(roles);
+  }
+
+  pub fn reset_role(&mut self) {
+    let mut roles = self.roles.clone();
+    roles.reset(self.selected_role());
+    self.change_roles(roles);
+  }
+
+  /// Selects the next (or previous) registered generator, keeping the picked parameter if it has it.
+  fn cycle_harmony(&mut self, next: bool) {
     if self.display_mode != DisplayMode::Palette {return}
-    match self.selected_harmony {
-      Harmony::Monochromatic => {self.selected_harmony = Harmony::Tetradic},
-      Harmony::Analogous => {self.selected_harmony = Harmony::Monochromatic},
-      Harmony::Complementary => {self.selected_harmony = Harmony::Analogous},
-      Harmony::SplitComplementary => {self.selected_harmony = Harmony::Complementary},
-      Harmony::Triadic => {self.selected_harmony = Harmony::SplitComplementary },
-      Harmony::Tetradic => {self.selected_harmony = Harmony::Triadic},   
+    let harmonies: Vec<Harmony> = Harmony::all().collect();
+    let n = harmonies.len();
+    let idx = harmonies.iter().position(|h| *h == self.selected_harmony).unwrap_or(0);
+    let param = self.selected_palette_param();
+    self.selected_harmony = harmonies[if next {(idx + 1) % n} else {(idx + n - 1) % n}];
+    let params = self.selected_harmony.generator().params();
+    self.palette_param = params.iter().position(|p| Some(*p) == param).unwrap_or(0);
+  }
+
+  pub fn palette_next(&mut self) {
+    self.cycle_harmony(true);
+  }
+
+  pub fn palette_previous(&mut self) {
+    self.cycle_harmony(false);
+  }
+
+
+
+
+
+  fn is_naming(&self) -> bool {
+    matches!(self.display_mode, DisplayMode::SavePrompt | DisplayMode::RenamePrompt)
+  }
+
+  fn refresh_palette_list(&mut self) {
+    let selected = self.palette_list.state.selected();
+    let mut palettes: Vec<PaletteDocument> = self.terminal_palette.iter().cloned().collect();
+    match self.palette_store.list() {
+      Ok(saved) => {palettes.extend(saved);},
+      Err(e) => {self.inputerr = format!("Could not list palettes: {}", e);},
+    }
+    self.palette_list = StatefulList::with_items(palettes);
+    if !self.palette_list.items.is_empty() {
+      let idx = selected.unwrap_or(0).min(self.palette_list.items.len() - 1);
+      self.palette_list.state.select(Some(idx));
     }
   }
 
+  fn is_terminal_entry(&self, idx: usize) -> bool {
+    idx == 0 && self.terminal_palette.is_some()
+  }
+
+  /// Name of the selected saved palette, the terminal's palette is not saved and cannot be renamed or deleted.
+  fn selected_palette_name(&self) -> Option<String> {
+    let idx = self.palette_list.state.selected().filter(|&i| !self.is_terminal_entry(i))?;
+    self.palette_list.items.get(idx).map(|p| p.name.clone())
+  }
+
+  /// Offers the colors read from the terminal at the top of the palette list.
+  fn offer_terminal_palette(&mut self, palette: PaletteDocument) {
+    self.terminal_palette = Some(palette);
+    self.display_mode = DisplayMode::Load;
+    self.refresh_palette_list();
+    self.palette_list.state.select(Some(0));
+    self.inputerr = "Read the terminal's colors, Enter imports them".to_string();
+  }
+
+  pub fn toggle_save_prompt(&mut self) {
+    if self.display_mode != DisplayMode::SavePrompt {
+      self.display_mode = DisplayMode::SavePrompt;
+      self.inputstr = "".to_string();
+      self.inputerr = "".to_string();
+    } else {
+      self.display_mode = DisplayMode::Normal;
+    }
+  }
+
+  pub fn toggle_load(&mut self) {
+    if self.display_mode != DisplayMode::Load {
+      self.display_mode = DisplayMode::Load;
+      self.inputerr = "".to_string();
+      self.refresh_palette_list();
+    } else {
+      self.display_mode = DisplayMode::Normal;
+    }
+  }
+
+  pub fn toggle_named_colors(&mut self) {
+    if self.display_mode != DisplayMode::NamedColors {
+      self.display_mode = DisplayMode::NamedColors;
+      self.inputstr = "".to_string();
+      self.inputerr = "".to_string();
+      self.refresh_named_list();
+    } else {
+      self.display_mode = DisplayMode::Normal;
+    }
+  }
+
+  /// Matches of the typed name, or the names nearest to the selected color while nothing is typed.
+  fn refresh_named_list(&mut self) {
+    let matches = if self.inputstr.is_empty() {
+      names::nearest_n(&self.get_color_by_mode(), 50)
+    } else {
+      names::search(&self.inputstr, 100)
+    };
+    self.named_list = StatefulList::with_items(matches.into_iter().cloned().collect());
+    if !self.named_list.items.is_empty() {
+      self.named_list.state.select(Some(0));
+    }
+  }
+
+  fn submit_named_color(&mut self) {
+    let Some(idx) = self.named_list.state.selected() else {return};
+    let Some(named) = self.named_list.items.get(idx).cloned() else {return};
+    let colors = self.make_colors_by_mode(named.color);
+    self.change_color(colors);
+    self.inputerr = format!("Changed to {}", named.name);
+    self.refresh_named_list();
+  }
+
+  /// Prompts where letters are typed instead of triggering hotkeys.
+  fn is_typing_color(&self) -> bool {
+    matches!(self.display_mode, DisplayMode::InputPrompt | DisplayMode::NamedColors)
+  }
+
+  /// The key that opened a color prompt closes it again while nothing is typed, after that it is text.
+  fn closes_color_prompt(&self, key: KeyEvent) -> bool {
+    let toggle = match self.display_mode {
+      DisplayMode::InputPrompt => {Action::InputPrompt},
+      DisplayMode::NamedColors => {Action::ToggleNamedColors},
+      _ => {return false},
+    };
+    self.inputstr.is_empty() && self.config.keybindings.get(&Mode::Home).and_then(|keymap| keymap.get(&vec![key])) == Some(&toggle)
+  }
+
+  pub fn delete_palette(&mut self) {
+    if self.display_mode != DisplayMode::Load {return}
+    let Some(name) = self.selected_palette_name() else {return};
+    // the first press only asks, a second one on the same palette deletes it
+    if self.delete_armed.as_ref() != Some(&name) {
+      self.inputerr = format!("Press Del again to delete {}", name);
+      self.delete_armed = Some(name);
+      return;
+    }
+    self.delete_armed = None;
+    match self.palette_store.delete(&name) {
+      Ok(()) => {self.inputerr = format!("Deleted {}", name);},
+      Err(e) => {self.inputerr = format!("{}", e);},
+    }
+    self.refresh_palette_list();
+  }
+
+  pub fn toggle_rename_prompt(&mut self) {
+    match self.display_mode {
+      DisplayMode::Load => {
+        let Some(name) = self.selected_palette_name() else {return};
+        self.display_mode = DisplayMode::RenamePrompt;
+        self.inputstr = name.clone();
+        self.inputerr = "".to_string();
+        self.rename_from = name;
+      },
+      DisplayMode::RenamePrompt => {
+        self.display_mode = DisplayMode::Load;
+        self.inputerr = "".to_string();
+      },
+      _ => {},
+    }
+  }
 
+  fn submit_save(&mut self) {
+    let name = self.inputstr.trim().to_string();
+    let mut doc = PaletteDocument::new(&name, self.colors.clone());
+    if let Some((harmony, base)) = self.palette_origin.clone() {
+      doc = doc.with_harmony(harmony, base);
+    }
+    doc = doc.with_roles(self.roles.clone());
+    match self.palette_store.save(&doc) {
+      Ok(()) => {
+        self.inputerr = format!("Saved {}", name);
+        self.display_mode = DisplayMode::Normal;
+      },
+      Err(e) => {self.inputerr = format!("{}", e);},
+    }
+  }
 
+  fn submit_rename(&mut self) {
+    let name = self.inputstr.trim().to_string();
+    let from = self.rename_from.clone();
+    if name == from {
+      self.display_mode = DisplayMode::Load;
+      return
+    }
+    match self.palette_store.rename(&from, &name) {
+      Ok(()) => {
+        self.inputerr = format!("Renamed {} to {}", from, name);
+        self.display_mode = DisplayMode::Load;
+        self.refresh_palette_list();
+      },
+      Err(e) => {self.inputerr = format!("{}", e);},
+    }
+  }
 
+  fn submit_load(&mut self) {
+    let Some(idx) = self.palette_list.state.selected() else {return};
+    let Some(palette) = self.palette_list.items.get(idx).cloned() else {return};
+    self.change_palette(palette.colors, palette.roles);
+    self.locked.clear();
+    if let (Some(harmony), Some(base)) = (palette.harmony, palette.base) {
+      self.palette_origin = Some((harmony, base));
+    }
+    self.inputerr = format!("Loaded {}", palette.name);
+  }
 
   pub fn add_to_inputstr(&mut self, ch: char) {
     self.inputstr.push(ch);
@@ -543,16 +1150,16 @@ impl Home {
     self.inputstr.pop();
   }
 
+  /// Parses the input as hex, r,g,b or any CSS color, the error names the part that failed.
   fn submit_input(&mut self) {
-    // parse input if rgb or hex
-    if self.input_mode == InputMode::HEX {
-      // parse input hex
-      self.parse_hex();
-    } else {
-      // parse input rgb
-      self.parse_rgb();
+    match ColorRGB::from_str(&self.inputstr) {
+      Ok(color) => {
+        let colors = self.make_colors_by_mode(color);
+        self.change_color(colors);
+        self.inputerr = "Changed Colors".to_string();
+      },
+      Err(err) => {self.inputerr = err;},
     }
-
   }
 
   fn submit_shade(&mut self) {
@@ -587,9 +1194,61 @@ impl Home {
 
   pub fn submit_palette(&mut self) {
     if self.display_mode != DisplayMode::Palette {return}
-    let color = self.get_color_by_mode();
-    let colors = generators::generate_palette_with_harmony(color, self.selected_harmony);
+    let color = self.palette_base();
+    let colors = self.generate_palette(self.selected_harmony);
+    let dropped = self.dropped_slots();
+    if !dropped.is_empty() {
+      self.status = format!("Dropped {}", dropped.join(", "));
+    }
     self.change_color(colors);
+    self.palette_origin = Some((self.selected_harmony, color));
+  }
+
+  /// Indices of the locked slots.
+  fn locked_slots(&self) -> Vec<usize> {
+    (0..self.colors.len()).filter(|&idx| self.locked.contains(self.colors.name(idx))).collect()
+  }
+
+  /// Color the palettes are generated from: the selected one, or if it is unlocked while others are locked,
+  /// the first locked foreground (else the background) so the new colors harmonize with the locked ones.
+  fn palette_base(&self) -> ColorRGB {
+    let locked = self.locked_slots();
+    if locked.is_empty() || locked.contains(&self.selected) {
+      return self.get_color_by_mode();
+    }
+    let idx = locked.iter().copied().find(|&idx| idx > 0).unwrap_or(locked[0]);
+    self.colors.color(idx).clone()
+  }
+
+  /// Number of colors generated: as set in the Palette popup, but enough for the last locked slot.
+  fn palette_count(&self) -> usize {
+    self.locked_slots().last().map_or(self.harmony_params.count, |&idx| self.harmony_params.count.max(idx + 1))
+  }
+
+  /// Names of the slots that applying a palette drops, as fewer colors were asked for.
+  fn dropped_slots(&self) -> Vec<&str> {
+    (self.palette_count()..self.colors.len()).map(|idx| self.colors.name(idx)).collect()
+  }
+
+  /// The slots recolored by `harmony`, the locked ones kept.
+  fn generate_palette(&self, harmony: Harmony) -> Colors {
+    let locked = self.locked_slots();
+    let count = self.palette_count();
+    let generated = generators::generate_palette_with_harmony(self.palette_base(), harmony, self.harmony_space, &self.harmony_params.with_count(count));
+    let mut colors = generators::keep_locked(&self.colors, &generated, &locked);
+    colors.truncate(count);
+    colors
+  }
+
+  /// Locks the selected slot so palette generation keeps it, or unlocks it.
+  pub fn toggle_lock(&mut self) {
+    let name = self.colors.name(self.selected).to_string();
+    if self.locked.remove(&name) {
+      self.status = format!("Unlocked {}", name);
+    } else {
+      self.status = format!("Locked {}", name);
+      self.locked.insert(name);
+    }
   }
 
   pub fn submit_input_by_displaymode(&mut self){
@@ -599,6 +1258,12 @@ impl Home {
           DisplayMode::HSV => {self.submit_hsv();},
           DisplayMode::Shades => {self.submit_shade();},
           DisplayMode::Palette => {self.submit_palette();},
+          DisplayMode::SavePrompt => {self.submit_save();},
+          DisplayMode::Load => {self.submit_load();},
+          DisplayMode::RenamePrompt => {self.submit_rename();},
+          DisplayMode::ContrastMatrix => {},
+          DisplayMode::NamedColors => {self.submit_named_color();},
+          DisplayMode::Roles => {self.reset_role();},
     }
   }
 
@@ -609,6 +1274,8 @@ impl Home {
       DisplayMode::HSV => {self.hsv_prev_input();},
       DisplayMode::Shades => {},
       DisplayMode::Palette => {self.palette_previous();},
+      DisplayMode::Roles => {self.cycle_role_slot(false);},
+      DisplayMode::SavePrompt | DisplayMode::Load | DisplayMode::RenamePrompt | DisplayMode::ContrastMatrix | DisplayMode::NamedColors => {},
     }
   }
 
@@ -619,6 +1286,8 @@ impl Home {
       DisplayMode::HSV => {self.hsv_next_input();},
       DisplayMode::Shades => {},
       DisplayMode::Palette => {self.palette_next();},
+      DisplayMode::Roles => {self.cycle_role_slot(true);},
+      DisplayMode::SavePrompt | DisplayMode::Load | DisplayMode::RenamePrompt | DisplayMode::ContrastMatrix | DisplayMode::NamedColors => {},
     }
   }
 
@@ -628,7 +1297,11 @@ impl Home {
       DisplayMode::InputPrompt => {},
       DisplayMode::HSV => {self.hsv_increase_by_mode();},
       DisplayMode::Shades => {self.shade_list.previous();},
-      DisplayMode::Palette => {},
+      DisplayMode::Palette => {self.palette_param_previous();},
+      DisplayMode::Load => {self.palette_list.previous();},
+      DisplayMode::NamedColors => {self.named_list.previous();},
+      DisplayMode::Roles => {self.role_list.previous();},
+      DisplayMode::SavePrompt | DisplayMode::RenamePrompt | DisplayMode::ContrastMatrix => {},
     }
   }
 
@@ -638,87 +1311,71 @@ impl Home {
       DisplayMode::InputPrompt => {},
       DisplayMode::HSV => {self.hsv_decrease_by_mode();},
       DisplayMode::Shades => {self.shade_list.next();},
-      DisplayMode::Palette => {},
+      DisplayMode::Palette => {self.palette_param_next();},
+      DisplayMode::Load => {self.palette_list.next();},
+      DisplayMode::NamedColors => {self.named_list.next();},
+      DisplayMode::Roles => {self.role_list.next();},
+      DisplayMode::SavePrompt | DisplayMode::RenamePrompt | DisplayMode::ContrastMatrix => {},
     }
   }
 
-  fn parse_rgb(&mut self) {
-
-   let reg = RGB_REGEX.get_or_init(|| Regex::new(r"(\d{1,3})").unwrap());
-    let parsestr = self.inputstr.clone();
-    if !parsestr.contains(',') {
-      self.inputerr = "Invalid RGB, no delimiter -> r, g, b ".to_string();
-      return         
-    }
-
-    let mut results = vec![];
-    for (_, [line]) in reg.captures_iter(&parsestr).map(|c| c.extract()) {
-        results.push(line);
+  pub fn increase_by_displaymode(&mut self) {
+    match self.display_mode {
+      DisplayMode::Roles => {self.shade_role(10);},
+      DisplayMode::Palette => {self.change_palette_param(true);},
+      DisplayMode::Normal | DisplayMode::InputPrompt | DisplayMode::HSV | DisplayMode::Shades
+      | DisplayMode::SavePrompt | DisplayMode::Load | DisplayMode::RenamePrompt | DisplayMode::ContrastMatrix | DisplayMode::NamedColors => {},
     }
-    if results.len() == 3 {
-      let mut vals: Vec<u8> = vec![];
-      for res in results {
-        let val = res.parse::<u8>();
-        if val.is_err() {
-          self.inputerr = format!("Invalid Value: {}", res);
-          return  
-        } else {
-          vals.push(val.unwrap());
-        }       
-      }
-      let color = ColorRGB::new(vals[0], vals[1], vals[2]);
-      let colors = self.make_colors_by_mode(color);
-      self.change_color(colors);
-      self.inputerr = "Changed Colors".to_string();
-    } else {
+  }
 
-      self.inputerr = "Invalid RGB".to_string();
-      return      
+  pub fn decrease_by_displaymode(&mut self) {
+    match self.display_mode {
+      DisplayMode::Roles => {self.shade_role(-10);},
+      DisplayMode::Palette => {self.change_palette_param(false);},
+      DisplayMode::Normal | DisplayMode::InputPrompt | DisplayMode::HSV | DisplayMode::Shades
+      | DisplayMode::SavePrompt | DisplayMode::Load | DisplayMode::RenamePrompt | DisplayMode::ContrastMatrix | DisplayMode::NamedColors => {},
     }
+  }
 
-    // (\d{1,3})
+  fn change_color(&mut self, colors:Colors) {
+    self.change_palette(colors, self.roles.clone());
   }
 
-  fn parse_hex(&mut self) {
-    let parsestr: String;
-    if self.inputstr.contains('#') {
-      parsestr = self.inputstr.clone();
-    } else {
-      parsestr = format!("#{}",self.inputstr);
-    }
-    let color = ColorRGB::from_hex(&parsestr);
-    if color.is_err() {
-      self.inputerr = "Invalid HEX".to_string();
-      return
-    } else {
-      let color = color.unwrap();
-      let colors = self.make_colors_by_mode(color);
-      self.change_color(colors);
-      self.inputerr = "Changed Colors".to_string();
-    }
+  fn change_roles(&mut self, roles: Roles) {
+    self.change_palette(self.colors.clone(), roles);
   }
 
-  fn change_color(&mut self, colors:Colors) {
+  /// Changes colors and roles as one undoable step.
+  fn change_palette(&mut self, colors: Colors, roles: Roles) {
     self.inputstr = "".to_string();
-    self.color_history.push(self.colors.clone());
+    self.color_history.push((self.colors.clone(), self.roles.clone()));
     self.colors = colors;
+    self.roles = roles;
+    self.palette_origin = None;
+    self.selected = self.selected.min(self.colors.len() - 1);
     self.shade_list = self.create_shade_list();
   }
 
   fn undo_change(&mut self) {
     let last = self.color_history.pop();
-    if last.is_some() {
-      self.redo_history.push(self.colors.clone());
-      self.colors = last.unwrap();
+    if let Some((colors, roles)) = last {
+      self.redo_history.push((self.colors.clone(), self.roles.clone()));
+      self.colors = colors;
+      self.roles = roles;
+      self.palette_origin = None;
+      self.selected = self.selected.min(self.colors.len() - 1);
       self.shade_list = self.create_shade_list();
     }
   }
 
   fn redo_change(&mut self) {
     let next = self.redo_history.pop();
-    if next.is_some() {
-      self.color_history.push(self.colors.clone());
-      self.colors = next.unwrap();
+    if let Some((colors, roles)) = next {
+      self.color_history.push((self.colors.clone(), self.roles.clone()));
+      self.colors = colors;
+      self.roles = roles;
+      self.palette_origin = None;
+      self.selected = self.selected.min(self.colors.len() - 1);
       self.shade_list = self.create_shade_list();
     }
   }
@@ -748,8 +1405,8 @@ impl Home {
     } else {self._anim_rect.clone()};
     //self._anim_cube.rotate(15.0, 'z');
     canvas::Canvas::default()
-    .background_color(self.colors.background.color)
-    .block(Block::default().borders(Borders::ALL).title("").bg(self.colors.background.color).fg(self.colors.background.flip_rgb()))
+    .background_color(self.colors.background().color)
+    .block(Block::default().borders(Borders::ALL).title("").bg(self.colors.background().color).fg(self.colors.background().flip_rgb()))
     .marker(self.marker_type)
     .paint(move |ctx| {
 
@@ -763,68 +1420,61 @@ impl Home {
 
   }
 
+  /// Swaps the selected color with the one above, the names stay in place.
   pub fn color_up_by_selection(&mut self) {
     let mut colors = self.colors.clone();
-    match self.input_selector {
-      InputSelector::Background => {
-        colors.highlight = self.colors.background.clone(); 
-        colors.background = self.colors.highlight.clone(); 
-        self.input_selector = InputSelector::Highlight;
-      },
-      InputSelector::A => {
-        colors.background = self.colors.color_a.clone(); 
-        colors.color_a = self.colors.background.clone(); 
-        self.input_selector = InputSelector::Background;
-      },
-      InputSelector::B => {
-        colors.color_a = self.colors.color_b.clone(); 
-        colors.color_b = self.colors.color_a.clone(); 
-        self.input_selector = InputSelector::A;        
-      },
-      InputSelector::C => {
-        colors.color_b = self.colors.color_c.clone(); 
-        colors.color_c = self.colors.color_b.clone(); 
-        self.input_selector = InputSelector::B;         
-      },
-      InputSelector::Highlight => {
-        colors.color_c = self.colors.highlight.clone(); 
-        colors.highlight = self.colors.color_c.clone(); 
-        self.input_selector = InputSelector::C;
-      },
-    }
+    let above = (self.selected + colors.len() - 1) % colors.len();
+    colors.swap_colors(self.selected, above);
+    self.selected = above;
     self.change_color(colors);
   }
 
+  /// Swaps the selected color with the one below, the names stay in place.
   pub fn color_down_by_selection(&mut self) {
     let mut colors = self.colors.clone();
-    match self.input_selector {
-      InputSelector::Background => {
-        colors.color_a = self.colors.background.clone(); 
-        colors.background = self.colors.color_a.clone(); 
-        self.input_selector = InputSelector::A;
-      },
-      InputSelector::A => {
-        colors.color_b = self.colors.color_a.clone(); 
-        colors.color_a = self.colors.color_b.clone(); 
-        self.input_selector = InputSelector::B;
-      },
-      InputSelector::B => {
-        colors.color_c = self.colors.color_b.clone(); 
-        colors.color_b = self.colors.color_c.clone(); 
-        self.input_selector = InputSelector::C;        
+    let below = (self.selected + 1) % colors.len();
+    colors.swap_colors(self.selected, below);
+    self.selected = below;
+    self.change_color(colors);
+  }
+
+  /// Adds a copy of the selected color below it and selects the copy.
+  pub fn add_slot(&mut self) {
+    let mut colors = self.colors.clone();
+    match colors.insert(self.selected + 1, self.get_color_by_mode()) {
+      Ok(()) => {
+        self.selected += 1;
+        self.status = format!("Added {}", colors.name(self.selected));
+        self.change_color(colors);
       },
-      InputSelector::C => {
-        colors.highlight = self.colors.color_c.clone(); 
-        colors.color_c = self.colors.highlight.clone(); 
-        self.input_selector = InputSelector::Highlight;         
+      Err(e) => {self.status = e;},
+    }
+  }
+
+  pub fn remove_slot(&mut self) {
+    let mut colors = self.colors.clone();
+    match colors.remove(self.selected) {
+      Ok(slot) => {
+        self.status = format!("Removed {}", slot.name);
+        self.locked.remove(&slot.name);
+        self.change_color(colors);
       },
-      InputSelector::Highlight => {
-        colors.background = self.colors.highlight.clone(); 
-        colors.highlight = self.colors.background.clone(); 
-        self.input_selector = InputSelector::Background;
+      Err(e) => {self.status = e;},
+    }
+  }
+
+  /// Moves the selected slot with its name past its neighbor, among the foregrounds.
+  pub fn move_slot(&mut self, up: bool) {
+    let to = if up {self.selected.saturating_sub(1)} else {self.selected + 1};
+    if to >= self.colors.len() {return}
+    let mut colors = self.colors.clone();
+    match colors.move_slot(self.selected, to) {
+      Ok(()) => {
+        self.selected = to;
+        self.change_color(colors);
       },
+      Err(e) => {self.status = e;},
     }
-    self.change_color(colors);
   }
 
 }
@@ -841,68 +1491,60 @@ impl Component for Home{
   }
 
   fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
-    if self.display_mode == DisplayMode::InputPrompt {
-      match self.input_mode {
-        InputMode::HEX => {      
-          match key.code {
-            KeyCode::Backspace => {self.rm_last_char_from_inputstr();}
-            KeyCode::Char(keychar) => {
-              match keychar {
-                '0' => {self.add_to_inputstr('0');},
-                '1' => {self.add_to_inputstr('1');},
-                '2' => {self.add_to_inputstr('2');},
-                '3' => {self.add_to_inputstr('3');},
-                '4' => {self.add_to_inputstr('4');},
-                '5' => {self.add_to_inputstr('5');},
-                '6' => {self.add_to_inputstr('6');},
-                '7' => {self.add_to_inputstr('7');},
-                '8' => {self.add_to_inputstr('8');},
-                '9' => {self.add_to_inputstr('9');},
-                'A'|'a' => {self.add_to_inputstr('a');},
-                'B'|'b' => {self.add_to_inputstr('b');},
-                'C'|'c' => {self.add_to_inputstr('c');},
-                'D'|'d' => {self.add_to_inputstr('d');},
-                'E'|'e' => {self.add_to_inputstr('e');},
-                'F'|'f' => {self.add_to_inputstr('f');},
-                '#' => {self.add_to_inputstr('#');},
-                _ => {self.input.handle_event(&crossterm::event::Event::Key(key));}
-              }
-            return Ok(Some(Action::Render))
-            },
-            _ => {self.input.handle_event(&crossterm::event::Event::Key(key));}       
+    if self.is_naming() {
+      match key.code {
+        KeyCode::Backspace => {self.rm_last_char_from_inputstr();},
+        KeyCode::Char(keychar) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+          if keychar.is_alphanumeric() || matches!(keychar, ' ' | '-' | '_') {
+            self.add_to_inputstr(keychar);
           }
         },
-        InputMode::RGB => {
-          match key.code {
-            KeyCode::Backspace => {self.rm_last_char_from_inputstr();}
-            KeyCode::Char(keychar) => {
-              match keychar {
-                '0' => {self.add_to_inputstr('0');},
-                '1' => {self.add_to_inputstr('1');},
-                '2' => {self.add_to_inputstr('2');},
-                '3' => {self.add_to_inputstr('3');},
-                '4' => {self.add_to_inputstr('4');},
-                '5' => {self.add_to_inputstr('5');},
-                '6' => {self.add_to_inputstr('6');},
-                '7' => {self.add_to_inputstr('7');},
-                '8' => {self.add_to_inputstr('8');},
-                '9' => {self.add_to_inputstr('9');},
-                ',' => {self.add_to_inputstr(',');},
-                '(' => {self.add_to_inputstr('(');},
-                ')' => {self.add_to_inputstr(')');},
-                _ => {self.input.handle_event(&crossterm::event::Event::Key(key));}
-              }
-              return Ok(Some(Action::Render))
-            },
-            _ => {self.input.handle_event(&crossterm::event::Event::Key(key));}
-          }
-        }
-      };
+        _ => {return Ok(None)},
+      }
+      return Ok(Some(Action::Render))
+    }
+    self.typed_text = false;
+    if self.is_typing_color() {
+      match key.code {
+        KeyCode::Backspace => {self.rm_last_char_from_inputstr();},
+        KeyCode::Char(_) if self.closes_color_prompt(key) => {return Ok(None)},
+        KeyCode::Char(keychar) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+          self.add_to_inputstr(keychar);
+          self.typed_text = true;
+        },
+        _ => {return Ok(None)},
+      }
+      if self.display_mode == DisplayMode::NamedColors {
+        self.refresh_named_list();
+      }
+      return Ok(Some(Action::Render))
     }
     Ok(None)
   }
 
   fn update(&mut self, action: Action) -> Result<Option<Action>> {
+    // while a name is typed, plain keys are text and not hotkeys
+    if self.is_naming() {
+      match action {
+        Action::Tick | Action::Render | Action::Resize(..) | Action::SubmitInput | Action::SavePalette | Action::RenamePalette => {},
+        _ => {return Ok(None)},
+      }
+    }
+    // while a color is typed, letters are part of it (`hsl(`, `aqua`), only keys that are no text pass
+    if self.is_typing_color() {
+      match action {
+        Action::InputPrompt | Action::ToggleNamedColors if self.typed_text => {return Ok(None)},
+        Action::Tick | Action::Render | Action::Resize(..) | Action::SubmitInput | Action::InputPrompt | Action::ToggleNamedColors
+        | Action::NextColor | Action::PreviousColor | Action::ChangeUndo | Action::ChangeRedo
+        | Action::SelectLeft | Action::SelectRight | Action::SelectUp | Action::SelectDown => {},
+        _ => {return Ok(None)},
+      }
+    }
+    // any other key takes back a pending delete
+    match action {
+      Action::Tick | Action::Render | Action::Resize(..) | Action::DeletePalette => {},
+      _ => {if self.delete_armed.take().is_some() {self.inputerr = "".to_string();}},
+    }
     match action {
       // Actions that should always work, no matter the mode
       Action::Tick => {
@@ -926,37 +1568,80 @@ impl Component for Home{
 
       Action::ColorUp => {self.color_up_by_selection();},
       Action::ColorDown => {self.color_down_by_selection();},
+      Action::AddSlot => {self.add_slot();},
+      Action::RemoveSlot => {self.remove_slot();},
+      Action::MoveSlotUp => {self.move_slot(true);},
+      Action::MoveSlotDown => {self.move_slot(false);},
 
-      Action::TogglePalette => {if self.display_mode != DisplayMode::Palette {self.display_mode = DisplayMode::Palette; self.hsv_color = self.get_color_by_mode();} else {self.display_mode = DisplayMode::Normal};}
+      Action::ToggleNamedColors => {self.toggle_named_colors();},
+      Action::ToggleContrastMatrix => {if self.display_mode != DisplayMode::ContrastMatrix {self.display_mode = DisplayMode::ContrastMatrix} else {self.display_mode = DisplayMode::Normal};},
+
+      Action::TogglePalette => {if self.display_mode != DisplayMode::Palette {self.display_mode = DisplayMode::Palette; self.hsv_color = self.get_color_by_mode(); self.harmony_params.count = self.colors.len();} else {self.display_mode = DisplayMode::Normal};}
+      Action::ToggleLock => {self.toggle_lock();},
+      Action::ToggleRoles => {if self.display_mode != DisplayMode::Roles {self.display_mode = DisplayMode::Roles} else {self.display_mode = DisplayMode::Normal};},
+      Action::IncreaseValue => {self.increase_by_displaymode();},
+      Action::DecreaseValue => {self.decrease_by_displaymode();},
 
       Action::SelectLeft => {self.select_left_by_displaymode();},
       Action::SelectRight => {self.select_right_by_displaymode();},
       Action::SelectUp => {self.select_up_by_displaymode();},
       Action::SelectDown => {self.select_down_by_displaymode();},      
 
+      Action::SwitchColorModel => {self.switch_color_model();},
+      Action::ToggleContrastMetric => {self.toggle_contrast_metric();},
+      Action::FixContrast => {self.fix_contrast();},
+      Action::CycleColorDepth => {self.cycle_preview_depth();},
+      Action::SimulateCvd => {self.cycle_cvd();},
+      Action::ToggleContrastTarget => {self.toggle_contrast_target();},
+
+      Action::SavePalette => {self.toggle_save_prompt();},
+      Action::LoadPalette => {self.toggle_load();},
+      Action::DeletePalette => {self.delete_palette();},
+      Action::RenamePalette => {self.toggle_rename_prompt();},
+      Action::ReadTerminalPalette => {return Ok(Some(Action::QueryTerminalPalette))},
+      Action::TerminalPalette(palette) => {self.offer_terminal_palette(palette);},
+      Action::Error(e) => {self.status = e;},
+      Action::ToggleLiveApply => {return Ok(self.toggle_live_apply())},
+      // a new terminal was set up, it has its own colors again
+      Action::Resume => {self.applied = None;},
+
       _ => {}, // pass the remaining functions here to match mode before proceeding further
     }
-    Ok(None)
+    Ok(self.sync_live_apply())
   }
 
   fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+    // the swatches, paragraph and canvas are drawn with the composited (and simulated) colors, the popups with the real ones
+    let labels: Vec<String> = self.colors.slots().iter().map(|slot| {
+      let label = slot_label(&slot.name, &slot.color);
+      if self.locked.contains(&slot.name) {format!("{} *", label)} else {label}
+    }).collect();
+    let shown = self.colors.composited();
+    let shown = match self.cvd {
+      Some(cvd) => {shown.simulate_cvd(cvd)},
+      None => {shown},
+    };
+    let real_colors = std::mem::replace(&mut self.colors, shown);
     let layout = Layout::default()
       .direction(Direction::Vertical)
       .constraints([Constraint::Percentage(15), Constraint::Percentage(50), Constraint::Percentage(35)])
       .split(f.size());
 
+    // as many input boxes as fit, scrolled so the selected one is shown
+    let rows = self.colors.len().min((layout[2].height as usize / 3).max(1));
+    let first = self.selected.saturating_sub(rows - 1);
     let input_layout = Layout::default()
       .direction(Direction::Vertical)
-      .constraints([Constraint::Percentage(20),Constraint::Percentage(20),Constraint::Percentage(20),Constraint::Percentage(20),Constraint::Percentage(20),])
+      .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
       .split(layout[2]);
 
-    f.render_widget(self.create_input_box(InputSelector::Background, self.colors.background.clone(), "Background".to_string()), input_layout[0]);
-    f.render_widget(self.create_input_box(InputSelector::A, self.colors.color_a.clone(), "A".to_string()), input_layout[1]);
-    f.render_widget(self.create_input_box(InputSelector::B, self.colors.color_b.clone(), "B".to_string()), input_layout[2]);
-    f.render_widget(self.create_input_box(InputSelector::C, self.colors.color_c.clone(), "C".to_string()), input_layout[3]);
-    f.render_widget(self.create_input_box(InputSelector::Highlight, self.colors.highlight.clone(), "D".to_string()), input_layout[4]);
+    for (row, (idx, label)) in labels.into_iter().enumerate().skip(first).take(rows).enumerate() {
+      f.render_widget(self.create_input_box(idx, self.colors.color(idx).clone(), label), input_layout[row]);
+    }
 
-    f.render_widget(self.create_styled_paragraph().alignment(Alignment::Center), layout[0]);
+    let lines = layout[0].height.saturating_sub(2) as usize;
+    let scroll = self.selected.saturating_sub(lines.saturating_sub(1)) as u16;
+    f.render_widget(self.create_styled_paragraph().alignment(Alignment::Center).scroll((scroll, 0)), layout[0]);
 
     f.render_widget(self.create_canvas(&layout[1]), layout[1]);
 
@@ -975,17 +1660,28 @@ impl Component for Home{
       .constraints([Constraint::Percentage(10), Constraint::Percentage(80), Constraint::Percentage(10)])
       .split(canvaslayout[1]);
 
+    // a swatch per foreground, with a one row gap below each if there is room
+    let swatches = (self.colors.len() - 1) as u32;
     let blocklayout = Layout::default()
       .direction(Direction::Vertical)
-      .constraints([Constraint::Percentage(2), Constraint::Percentage(20), Constraint::Percentage(5), Constraint::Percentage(20), Constraint::Percentage(5), Constraint::Percentage(20), Constraint::Percentage(5), Constraint::Percentage(20), Constraint::Percentage(2)])
-      .split(div[1]);
+      .constraints(vec![Constraint::Ratio(1, swatches); swatches as usize])
+      .split(div[1].inner(&Margin { vertical: 1, horizontal: 0 }));
 
-    f.render_widget(Paragraph::new("").bg(self.colors.background.color), div[1]);
-    f.render_widget(Paragraph::new("").bg(self.colors.color_a.color), blocklayout[1]);
-    f.render_widget(Paragraph::new("").bg(self.colors.color_b.color), blocklayout[3]);
-    f.render_widget(Paragraph::new("").bg(self.colors.color_c.color), blocklayout[5]);
-    f.render_widget(Paragraph::new("").bg(self.colors.highlight.color), blocklayout[7]);
+    f.render_widget(Paragraph::new("").bg(self.colors.background().color), div[1]);
+    for (area, color) in blocklayout.iter().zip(self.colors.foregrounds()) {
+      let swatch = if area.height > 1 {Rect { height: area.height - 1, ..*area }} else {*area};
+      f.render_widget(Paragraph::new("").bg(color.color), swatch);
+    }
+
+    // the roles in use, below the rectangle
+    let preview = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Percentage(50), Constraint::Percentage(45), Constraint::Percentage(5)])
+      .split(canvaslayout[4])[1]
+      .inner(&Margin { vertical: 0, horizontal: 2 });
+    f.render_widget(self.role_preview(preview.width.saturating_sub(2) as usize), preview);
 
+    self.colors = real_colors;
 
     match self.display_mode {
       DisplayMode::Normal => {},
@@ -1006,11 +1702,11 @@ impl Component for Home{
           }
           ListItem::new(line)}).collect();
           let color = self.get_color_by_mode();
-          let isbkg = if self.input_selector == InputSelector::Background {true} else {false};
+          let isbkg = self.selected == 0;
           let titlestr = format!(" Shades for {} ", color.color.to_string());
           let shadelist = List::new( shadelines) //home.styledio.clone()
             .block(Block::default()
-              .bg(self.colors.background.color)
+              .bg(self.colors.background().color)
               .borders(Borders::ALL)
               .border_style(Style::new().fg(
                 if isbkg {
@@ -1021,7 +1717,7 @@ impl Component for Home{
               ))
               .title(block::Title::from(titlestr).alignment(Alignment::Left))
             )
-            .highlight_style(Style::new().fg(self.colors.highlight.color))
+            .highlight_style(Style::new().fg(self.colors.accent().color))
             .highlight_symbol(">> ");
 
 
@@ -1038,10 +1734,34 @@ impl Component for Home{
       },
       DisplayMode::Palette => {
         f.render_widget(Clear, popuplayout[1]);
-        f.render_widget(self.popup_palette(), popuplayout[1]);
-      }
+        self.popup_palette(f, popuplayout[1]);
+      },
+      DisplayMode::SavePrompt | DisplayMode::RenamePrompt => {
+        let centered = centered_rect(popuplayout[1], 50, 30);
+        f.render_widget(Clear, centered);
+        f.render_widget(self.popup_name_prompt(), centered);
+      },
+      DisplayMode::Load => {
+        f.render_widget(Clear, popuplayout[1]);
+        self.popup_load(f, popuplayout[1]);
+      },
+      DisplayMode::ContrastMatrix => {
+        let centered = centered_rect(layout[1], 90, 90);
+        f.render_widget(Clear, centered);
+        self.popup_contrast_matrix(f, centered);
+      },
+      DisplayMode::NamedColors => {
+        f.render_widget(Clear, popuplayout[1]);
+        self.popup_named_colors(f, popuplayout[1]);
+      },
+      DisplayMode::Roles => {
+        f.render_widget(Clear, popuplayout[1]);
+        self.popup_roles(f, popuplayout[1]);
+      },
     };
 
+    // everything drawn is downgraded to what the terminal can show
+    quantize_buffer(f.buffer_mut(), self.preview_depth.lower(self.color_depth));
     Ok(())
   }
 }
@@ -1065,3 +1785,86 @@ pub fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
     ])
     .split(popup_layout[1])[1]
 }
+
+/// Label of a slot's input box, with its opacity if translucent.
+fn slot_label(name: &str, color: &ColorRGB) -> String {
+  if color.is_opaque() {
+    return name.to_string();
+  }
+  format!("{} {}%", name, (color.a as f64 / 255.0 * 100.0).round())
+}
+
+/// Replaces the colors of every cell with the ones a terminal of `depth` shows.
+fn quantize_buffer(buffer: &mut Buffer, depth: ColorDepth) {
+  if depth == ColorDepth::TrueColor {
+    return;
+  }
+  let mut mapped: HashMap<Color, Color> = HashMap::new();
+  for cell in buffer.content.iter_mut() {
+    cell.fg = *mapped.entry(cell.fg).or_insert_with(|| depth.map(cell.fg));
+    cell.bg = *mapped.entry(cell.bg).or_insert_with(|| depth.map(cell.bg));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_undo_roles() {
+    let mut home = Home::new();
+    home.shade_role(10);
+    home.cycle_role_slot(true);
+    let edited = home.roles.clone();
+    assert!(!edited.is_empty());
+    home.undo_change();
+    home.undo_change();
+    assert_eq!(home.roles, Roles::default());
+    home.redo_change();
+    home.redo_change();
+    assert_eq!(home.roles, edited);
+
+    let mut loaded = PaletteDocument::new("dusk", home.colors.clone());
+    loaded.roles.set(Role::Accent, RoleSource::new("color_b", 0));
+    home.palette_list = StatefulList::with_items(vec![loaded.clone()]);
+    home.palette_list.state.select(Some(0));
+    home.submit_load();
+    assert_eq!(home.roles, loaded.roles);
+    home.undo_change();
+    assert_eq!(home.roles, edited);
+  }
+
+  #[test]
+  fn test_remove_locked_slot() {
+    let mut home = Home::new();
+    home.selected = 3;
+    home.toggle_lock();
+    let name = home.colors.name(3).to_string();
+    home.remove_slot();
+    assert!(!home.locked.contains(&name));
+    home.selected = 2;
+    home.add_slot();
+    assert_eq!(home.colors.name(3), name);
+    assert_eq!(home.locked_slots(), Vec::<usize>::new());
+  }
+  #[test]
+  fn test_delete_palette_asks_first() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("termcolors-delete-asks-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut home = Home::new();
+    home.palette_store = PaletteStore::with_dir(dir.clone());
+    home.palette_store.save(&PaletteDocument::new("dusk", home.colors.clone()))?;
+    home.toggle_load();
+    home.delete_palette();
+    assert!(home.palette_store.exists("dusk"));
+    home.update(Action::SelectDown)?;
+    home.delete_palette();
+    assert!(home.palette_store.exists("dusk"));
+    home.delete_palette();
+    assert!(!home.palette_store.exists("dusk"));
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
@@ -45,6 +45,8 @@ impl DRect {
   }
 
   pub fn draw_lines(&self, ctx: &mut canvas::Context, colors: &Colors) {
+    // the edges take the foreground colors in turn
+    let edges: Vec<Color> = colors.foregrounds().map(|c| c.color).cycle().take(4).collect();
     // Draw Rect
     // Bottom Left to Top Left
     ctx.draw(&canvas::Line {
@@ -52,7 +54,7 @@ impl DRect {
         y1: self.bottom_left.1,
         x2: self.top_left.0,
         y2: self.top_left.1,
-        color: colors.color_a.color,
+        color: edges[0],
     });
     // Top Left to Top Right
     ctx.draw(&canvas::Line {
@@ -60,7 +62,7 @@ impl DRect {
         y1: self.top_left.1,
         x2: self.top_right.0,
         y2: self.top_right.1,
-        color: colors.color_b.color,
+        color: edges[1],
     });
     // Top Right to Bottom Right
     ctx.draw(&canvas::Line {
@@ -68,7 +70,7 @@ impl DRect {
         y1: self.top_right.1,
         x2: self.bottom_right.0,
         y2: self.bottom_right.1,
-        color: colors.color_c.color,
+        color: edges[2],
     });
     // Bottom Right to Bottom Left
     ctx.draw(&canvas::Line {
@@ -76,7 +78,7 @@ impl DRect {
         y1: self.bottom_right.1,
         x2: self.bottom_left.0,
         y2: self.bottom_left.1,
-        color: colors.highlight.color,
+        color: edges[3],
     });
     // Bottom Left to Top Right
     ctx.draw(&canvas::Line {
@@ -84,7 +86,7 @@ impl DRect {
         y1: self.bottom_left.1,
         x2: self.top_right.0,
         y2: self.top_right.1,
-        color: colors.highlight.flip_rgb(),
+        color: colors.accent().flip_rgb(),
     });
   }
 
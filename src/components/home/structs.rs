@@ -198,6 +198,8 @@ impl DCube {
   pub fn draw_lines_orthographic(&self, ctx: &mut Context, colors: &Colors) {
     // Draw lines after applying orthographic projection
     // You can use the rotated coordinates here
+    // the edges of each face take the foreground colors in turn
+    let edges: Vec<Color> = colors.foregrounds().map(|c| c.color).cycle().take(4).collect();
 
     // Connect front face vertices
     ctx.draw(&canvas::Line {
@@ -205,28 +207,28 @@ impl DCube {
         y1: self.bottom_left_front.1,
         x2: self.bottom_right_front.0,
         y2: self.bottom_right_front.1,
-        color: colors.color_a.color,
+        color: edges[0],
     });
     ctx.draw(&canvas::Line {
         x1: self.bottom_right_front.0,
         y1: self.bottom_right_front.1,
         x2: self.top_right_front.0,
         y2: self.top_right_front.1,
-        color: colors.color_b.color,
+        color: edges[1],
     });
     ctx.draw(&canvas::Line {
         x1: self.top_right_front.0,
         y1: self.top_right_front.1,
         x2: self.top_left_front.0,
         y2: self.top_left_front.1,
-        color: colors.color_c.color,
+        color: edges[2],
     });
     ctx.draw(&canvas::Line {
         x1: self.top_left_front.0,
         y1: self.top_left_front.1,
         x2: self.bottom_left_front.0,
         y2: self.bottom_left_front.1,
-        color: colors.highlight.color,
+        color: edges[3],
     });
 
     // Connect back face vertices
@@ -235,28 +237,28 @@ impl DCube {
         y1: self.bottom_left_back.1,
         x2: self.bottom_right_back.0,
         y2: self.bottom_right_back.1,
-        color: colors.color_a.color,
+        color: edges[0],
     });
     ctx.draw(&canvas::Line {
         x1: self.bottom_right_back.0,
         y1: self.bottom_right_back.1,
         x2: self.top_right_back.0,
         y2: self.top_right_back.1,
-        color: colors.color_b.color,
+        color: edges[1],
     });
     ctx.draw(&canvas::Line {
         x1: self.top_right_back.0,
         y1: self.top_right_back.1,
         x2: self.top_left_back.0,
         y2: self.top_left_back.1,
-        color: colors.color_c.color,
+        color: edges[2],
     });
     ctx.draw(&canvas::Line {
         x1: self.top_left_back.0,
         y1: self.top_left_back.1,
         x2: self.bottom_left_back.0,
         y2: self.bottom_left_back.1,
-        color: colors.highlight.color,
+        color: edges[3],
     });
 
     // Connect corresponding vertices between front and back faces
@@ -265,28 +267,28 @@ impl DCube {
         y1: self.bottom_left_front.1,
         x2: self.bottom_left_back.0,
         y2: self.bottom_left_back.1,
-        color: colors.highlight.flip_rgb(),
+        color: colors.accent().flip_rgb(),
     });
     ctx.draw(&canvas::Line {
         x1: self.bottom_right_front.0,
         y1: self.bottom_right_front.1,
         x2: self.bottom_right_back.0,
         y2: self.bottom_right_back.1,
-        color: colors.highlight.flip_rgb(),
+        color: colors.accent().flip_rgb(),
     });
     ctx.draw(&canvas::Line {
         x1: self.top_left_front.0,
         y1: self.top_left_front.1,
         x2: self.top_left_back.0,
         y2: self.top_left_back.1,
-        color: colors.highlight.flip_rgb(),
+        color: colors.accent().flip_rgb(),
     });
     ctx.draw(&canvas::Line {
         x1: self.top_right_front.0,
         y1: self.top_right_front.1,
         x2: self.top_right_back.0,
         y2: self.top_right_back.1,
-        color: colors.highlight.flip_rgb(),
+        color: colors.accent().flip_rgb(),
     });
   }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ pub mod hsv;
 
 
 use ratatui::{prelude::*, widgets::*};
-use super::{ColorRGB, InputMode, StyledLine, get_contrast};
+use super::{ColorRGB, ContrastMetric, InputMode, StyledLine};
 
 pub fn pad_to_length(input: String, length: usize) -> String {
     format!("{:<width$}", input, width = length)
@@ -86,19 +86,19 @@ pub fn create_shade_lines(color:ColorRGB, bkgcolor:ColorRGB) -> Vec<Line<'static
     ]
   }
 
-pub fn create_styled_shade_line(color:ColorRGB, frac: f32, bkgcolor:ColorRGB) -> (StyledLine, String) {
+pub fn create_styled_shade_line(color:ColorRGB, frac: f32, bkgcolor:ColorRGB, metric: ContrastMetric) -> (StyledLine, String) {
     let shade = color.shade(frac);
     let shade_hex = shade.to_string();
     let shade_color = ColorRGB::from_hex(&shade_hex);
     let _rgb: String;
-    let contrast: f32;
+    let contrast: String;
     if shade_color.is_ok() {
       let shade_color = shade_color.unwrap();
-      contrast = get_contrast(&shade_color, &bkgcolor);
+      contrast = metric.describe(&shade_color, &bkgcolor);
       _rgb = pad_to_length(format!("({},{},{})", shade_color.r, shade_color.g, shade_color.b), 13);
     }
     else {
-      contrast = 0.0;
+      contrast = "".to_string();
       _rgb = "".to_string();
     }
     let mut res = StyledLine::default();
@@ -110,38 +110,45 @@ pub fn create_styled_shade_line(color:ColorRGB, frac: f32, bkgcolor:ColorRGB) ->
         (format!("    {}    ", _rgb), Style::new().fg(bkgcolor.flip_rgb())),
         (format!("    "), Style::new()),
         (format!(" Lorem ipsum "), Style::new().fg(shade)),
-        (format!("  {:.2}  ", contrast), Style::new().fg(bkgcolor.flip_rgb())),
+        (format!("  {}  ", contrast), Style::new().fg(bkgcolor.flip_rgb())),
     ];
     (res, shade.to_string())
   }
 
-pub fn create_styled_shade_lines(color:ColorRGB, bkgcolor:ColorRGB) -> Vec<(StyledLine, String)> {
+pub fn create_styled_shade_lines(color:ColorRGB, bkgcolor:ColorRGB, metric: ContrastMetric) -> Vec<(StyledLine, String)> {
     vec![
         (StyledLine{words: vec![(String::from(""), Style::default())]}, "".to_string()),
-      create_styled_shade_line(color.clone(), -0.9, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.8, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.7, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.6, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.5, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.4, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.3, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.2, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), -0.1, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.0, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.1, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.2, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.3, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.4, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.5, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.6, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.7, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.8, bkgcolor.clone()),
-      create_styled_shade_line(color.clone(), 0.9, bkgcolor.clone()),
+      create_styled_shade_line(color.clone(), -0.9, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.8, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.7, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.6, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.5, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.4, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.3, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.2, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), -0.1, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.0, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.1, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.2, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.3, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.4, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.5, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.6, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.7, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.8, bkgcolor.clone(), metric),
+      create_styled_shade_line(color.clone(), 0.9, bkgcolor.clone(), metric),
       (StyledLine{words: vec![(String::from(""), Style::default())]}, "".to_string()),
     ]
   }
 
-pub fn create_paragraph_line(text: String, color: ColorRGB, bkgcolor: ColorRGB) -> Line<'static> {
+/// Contrast and grade of a text color on the background, crossed out if it fails.
+pub fn create_contrast_span(color: &ColorRGB, bkgcolor: &ColorRGB, metric: ContrastMetric) -> Span<'static> {
+    let fails = metric.is_fail(metric.contrast(color, bkgcolor));
+    let style = if fails {Style::new().fg(bkgcolor.flip_rgb()).crossed_out()} else {Style::new().fg(bkgcolor.flip_rgb())};
+    Span::styled(format!(" Ctr: {} ", metric.describe(color, bkgcolor)), style)
+}
+
+pub fn create_paragraph_line(text: String, color: ColorRGB, bkgcolor: ColorRGB, metric: ContrastMetric) -> Line<'static> {
     const PADTO: usize = 10;
     let _text = format!("{}", pad_to_length(text, PADTO));
     let _rgb = pad_to_length(format!("({},{},{})", color.r, color.g, color.b), 13);
@@ -153,7 +160,7 @@ pub fn create_paragraph_line(text: String, color: ColorRGB, bkgcolor: ColorRGB)
       Span::styled(format!(" {} ", color.color.to_string()), Style::new().fg(bkgcolor.flip_rgb())),
       Span::styled(format!(" {} ", _rgb), Style::new().fg(bkgcolor.flip_rgb())),      
     ]);
-    line.spans.push(Span::styled(format!(" Ctr: {:.2} ", get_contrast(&color, &bkgcolor)), Style::new().fg(bkgcolor.flip_rgb())));
+    line.spans.push(create_contrast_span(&color, &bkgcolor, metric));
     let _hsv = color.rgb_to_hsv();
     let h = leftpad_to_length(format!("{:.0}", _hsv.0), 4);
     let s = pad_to_length(format!("{:.2}", _hsv.1), 4);
@@ -169,7 +176,7 @@ pub fn create_paragraph_line(text: String, color: ColorRGB, bkgcolor: ColorRGB)
     line.spans.push(Span::styled(format!(" {} ", _text), Style::new().bg(flip.color).fg(color.color)));
     line.spans.push(Span::styled(        "       ", Style::new()));
     line.spans.push(Span::styled(format!(" {} ", flip.color.to_string()), Style::new().fg(bkgcolor.flip_rgb())));
-    line.spans.push(Span::styled(format!(" Ctr: {:.2} ", get_contrast(&flip, &bkgcolor)), Style::new().fg(bkgcolor.flip_rgb())));
+    line.spans.push(create_contrast_span(&flip, &bkgcolor, metric));
     let _hsv = flip.rgb_to_hsv();
     let h = leftpad_to_length(format!("{:.0}", _hsv.0), 4);
     let s = pad_to_length(format!("{:.2}", _hsv.1), 4);
@@ -1,6 +1,7 @@
 use std::rc::Rc;
 use ratatui::{prelude::*, widgets::*};
-use super::{ColorRGB, InputMode, StyledLine, get_contrast};
+use super::{ColorRGB, InputMode, StyledLine};
+use crate::colors::perceptual::OKLCH_MAX_CHROMA;
 
 pub fn create_hue_column(color: &ColorRGB) -> Paragraph {
 
@@ -69,6 +70,69 @@ pub fn create_val_column(color: &ColorRGB) -> Paragraph {
 	Paragraph::new(p)
 }
 
+pub fn create_hsl_sat_column(color: &ColorRGB) -> Paragraph<'static> {
+
+	let p: Vec<Line> = vec![
+			Line::from(Span::styled("    ", Style::new())),
+			Line::from(Span::styled("    ", Style::new())),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(1.0).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.9).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.8).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.7).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.6).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.5).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.4).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.3).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.2).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.1).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_hsl_saturation(0.0).color))),
+	];
+	Paragraph::new(p)
+}
+
+pub fn create_light_column(color: &ColorRGB) -> Paragraph<'static> {
+
+	let p: Vec<Line> = vec![
+			Line::from(Span::styled("    ", Style::new())),
+			Line::from(Span::styled("    ", Style::new())),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(1.0).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.9).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.8).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.7).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.6).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.5).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.4).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.3).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.2).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.1).color))),
+			Line::from(Span::styled("    ", Style::new().bg(color.with_lightness(0.0).color))),
+	];
+	Paragraph::new(p)
+}
+
+/// Column of swatches from top to bottom, below two empty lines like the other columns.
+fn create_swatch_column(swatches: impl Iterator<Item = ColorRGB>) -> Paragraph<'static> {
+	let mut p: Vec<Line> = vec![
+			Line::from(Span::styled("    ", Style::new())),
+			Line::from(Span::styled("    ", Style::new())),
+	];
+	p.extend(swatches.map(|c| Line::from(Span::styled("    ", Style::new().bg(c.color)))));
+	Paragraph::new(p)
+}
+
+pub fn create_oklch_hue_column(color: &ColorRGB) -> Paragraph<'static> {
+	let hues = [359.0, 330.0, 300.0, 270.0, 240.0, 210.0, 180.0, 150.0, 120.0, 90.0, 60.0, 30.0, 0.0];
+	create_swatch_column(hues.into_iter().map(|h| color.with_oklch_hue(h)))
+}
+
+pub fn create_chroma_column(color: &ColorRGB) -> Paragraph<'static> {
+	create_swatch_column((0..=10).rev().map(|i| color.with_oklch_chroma(OKLCH_MAX_CHROMA * f64::from(i) / 10.0)))
+}
+
+pub fn create_oklch_light_column(color: &ColorRGB) -> Paragraph<'static> {
+	create_swatch_column((0..=10).rev().map(|i| color.with_oklch_lightness(f64::from(i) / 10.0)))
+}
+
 pub fn create_bar(bkgcolor: &ColorRGB, value: u64, max_val: u64, title: String, border_color: Color) -> impl Widget {
     let bar  = Bar::default()
 			.value(value)//value	
@@ -4,14 +4,21 @@ use color_eyre::eyre::Result;
 use config::Value;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::{
+  style::{Color, Modifier, Style},
+  symbols::Marker,
+};
 use serde::{
   de::{self, Deserializer, MapAccess, Visitor},
   Deserialize, Serialize,
 };
 use serde_json::Value as JsonValue;
 
-use crate::{action::Action, mode::Mode};
+use crate::{
+  action::Action,
+  colors::{self, generators::Harmony, perceptual::ColorSpace, ColorRGB, Colors, Slot},
+  mode::Mode,
+};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
@@ -31,6 +38,8 @@ pub struct Config {
   pub keybindings: KeyBindings,
   #[serde(default)]
   pub styles: Styles,
+  #[serde(default)]
+  pub palette: PaletteConfig,
 }
 
 impl Config {
@@ -74,11 +83,73 @@ impl Config {
         user_styles.entry(style_key.clone()).or_insert_with(|| style.clone());
       }
     }
+    cfg.palette.merge_defaults(default_config.palette);
+    if let Err(e) = cfg.palette.colors() {
+      return Err(config::ConfigError::Message(format!("invalid palette.colors: {}", e)));
+    }
 
     Ok(cfg)
   }
 }
 
+/// Startup state of the palette. Every field left out falls back to the embedded default config.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PaletteConfig {
+  #[serde(default)]
+  pub colors: PaletteColorsConfig,
+  #[serde(default)]
+  pub harmony: Option<Harmony>,
+  #[serde(default)]
+  pub space: Option<ColorSpace>,
+  #[serde(default, deserialize_with = "deserialize_marker")]
+  pub marker: Option<Marker>,
+  #[serde(default)]
+  pub spin: Option<bool>,
+}
+
+/// Configured slots by name, in the order of the config file.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PaletteColorsConfig(#[serde(deserialize_with = "colors::deserialize_slots")] pub Vec<Slot>);
+
+impl PaletteConfig {
+  pub fn merge_defaults(&mut self, defaults: PaletteConfig) {
+    // the user config comes without key order: slots the defaults lack are added by name
+    let mut slots = defaults.colors.0;
+    let mut added = vec![];
+    for slot in std::mem::take(&mut self.colors.0) {
+      match slots.iter_mut().find(|s| s.name == slot.name) {
+        Some(default) => {default.color = slot.color;},
+        None => {added.push(slot);},
+      }
+    }
+    added.sort_by(|a, b| a.name.cmp(&b.name));
+    slots.extend(added);
+    self.colors.0 = slots;
+    self.harmony = self.harmony.or(defaults.harmony);
+    self.space = self.space.or(defaults.space);
+    self.marker = self.marker.or(defaults.marker);
+    self.spin = self.spin.or(defaults.spin);
+  }
+
+  /// The configured colors, or why they make no valid palette.
+  pub fn colors(&self) -> Result<Colors, String> {
+    Colors::new(self.colors.0.clone())
+  }
+}
+
+fn deserialize_marker<'de, D>(deserializer: D) -> Result<Option<Marker>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let Some(raw) = Option::<String>::deserialize(deserializer)? else { return Ok(None) };
+  let normalized = raw.to_lowercase().replace(['-', '_', ' '], "");
+  [Marker::Bar, Marker::Block, Marker::Braille, Marker::Dot, Marker::HalfBlock]
+    .into_iter()
+    .find(|m| m.to_string().to_lowercase() == normalized)
+    .map(Some)
+    .ok_or_else(|| de::Error::custom(format!("invalid marker `{}`, expected one of: bar, block, braille, dot, halfblock", raw)))
+}
+
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct KeyBindings(pub HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>);
 
@@ -388,6 +459,7 @@ fn parse_color(s: &str) -> Option<Color> {
 
 #[cfg(test)]
 mod tests {
+  use color_eyre::eyre::eyre;
   use pretty_assertions::assert_eq;
 
   use super::*;
@@ -449,6 +521,64 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn test_palette_defaults() -> Result<()> {
+    let c = Config::new()?;
+    assert!(c.palette.colors().is_ok());
+    assert!(c.palette.harmony.is_some());
+    assert!(c.palette.space.is_some());
+    assert!(c.palette.marker.is_some());
+    assert!(c.palette.spin.is_some());
+    Ok(())
+  }
+
+  fn config_from_json5(s: &str) -> Result<Config, config::ConfigError> {
+    config::Config::builder().add_source(config::File::from_str(s, config::FileFormat::Json5)).build()?.try_deserialize()
+  }
+
+  #[test]
+  fn test_palette_merge() -> Result<()> {
+    let mut c = config_from_json5(r##"{ "palette": { "colors": { "color_b": "#00EEEC" }, "marker": "half_block" } }"##)?;
+    let default_config: Config = json5::from_str(CONFIG).unwrap();
+    c.palette.merge_defaults(default_config.palette.clone());
+    let colors = c.palette.colors().map_err(|e| eyre!(e))?;
+    let defaults = default_config.palette.colors().map_err(|e| eyre!(e))?;
+    assert_eq!(colors.by_name("color_b"), Some(&ColorRGB::new(0, 238, 236)));
+    assert_eq!(colors.background(), defaults.background());
+    assert_eq!(c.palette.marker, Some(Marker::HalfBlock));
+    assert_eq!(c.palette.spin, default_config.palette.spin);
+    Ok(())
+  }
+
+  #[test]
+  fn test_palette_extra_slots() -> Result<()> {
+    let mut c = config_from_json5(r##"{ "palette": { "colors": { "warning": "#FFAA00", "color_d": "#00EEEC" } } }"##)?;
+    let default_config: Config = json5::from_str(CONFIG).unwrap();
+    c.palette.merge_defaults(default_config.palette.clone());
+    let colors = c.palette.colors().map_err(|e| eyre!(e))?;
+    assert_eq!(colors.len(), 7);
+    assert_eq!(colors.name(5), "color_d");
+    assert_eq!(colors.name(6), "warning");
+    Ok(())
+  }
+
+  #[test]
+  fn test_palette_invalid() -> Result<()> {
+    let mut c = config_from_json5(r##"{ "palette": { "colors": { " ": "#FFAA00" } } }"##)?;
+    let default_config: Config = json5::from_str(CONFIG).unwrap();
+    c.palette.merge_defaults(default_config.palette);
+    assert_eq!(c.palette.colors(), Err("a palette color has an empty name".to_string()));
+    Ok(())
+  }
+
+  #[test]
+  fn test_palette_invalid_hex() {
+    let err = config_from_json5(r##"{ "palette": { "colors": { "background": "#20202" } } }"##).unwrap_err();
+    assert!(err.to_string().contains("invalid color `#20202`"), "{}", err);
+    let err = config_from_json5(r##"{ "palette": { "marker": "sparkles" } }"##).unwrap_err();
+    assert!(err.to_string().contains("invalid marker `sparkles`"), "{}", err);
+  }
+
   #[test]
   fn test_simple_keys() {
     assert_eq!(parse_key_event("a").unwrap(), KeyEvent::new(KeyCode::Char('a'), KeyModifiers::empty()));
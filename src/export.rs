@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use color_eyre::eyre::Result;
+use serde::Serialize;
+
+use crate::colors::{roles::Role, schema::{PaletteDocument, PaletteFormat}, ColorRGB};
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum ExportFormat {
+  /// One `slot #RRGGBB` line per slot
+  #[default]
+  Hex,
+  /// CSS custom properties on `:root`
+  Css,
+  /// Palette document as JSON
+  Json,
+  /// Palette document as TOML
+  Toml,
+  /// Palette document as YAML
+  Yaml,
+}
+
+/// Slots of a palette document in display order, with their names.
+pub fn named_slots(doc: &PaletteDocument) -> Vec<(&str, &ColorRGB)> {
+  doc.colors.named_slots()
+}
+
+/// Roles as written to JSON, TOML and YAML: the palette name and one hex string per role.
+#[derive(Serialize)]
+struct RolesDocument<'a> {
+  name: &'a str,
+  roles: BTreeMap<Role, ColorRGB>,
+}
+
+/// One `name  #RRGGBB` line per color, the hex values aligned after the longest name.
+fn hex_lines<'a>(colors: impl IntoIterator<Item = (&'a str, &'a ColorRGB)>) -> String {
+  let colors: Vec<(&str, &ColorRGB)> = colors.into_iter().collect();
+  let width = colors.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0).max(11);
+  colors.iter().map(|(name, color)| format!("{:<width$} {}\n", name, color.to_hex(), width = width)).collect()
+}
+
+fn css_properties<'a>(colors: impl IntoIterator<Item = (&'a str, &'a ColorRGB)>) -> String {
+  let mut s = String::from(":root {\n");
+  for (name, color) in colors {
+    s.push_str(&format!("  --{}: {};\n", name.replace('_', "-"), color.to_hex().to_lowercase()));
+  }
+  s.push_str("}\n");
+  s
+}
+
+pub fn export_palette(doc: &PaletteDocument, format: ExportFormat) -> Result<String> {
+  let s = match format {
+    ExportFormat::Hex => hex_lines(named_slots(doc)),
+    ExportFormat::Css => css_properties(named_slots(doc)),
+    ExportFormat::Json => doc.to_string_as(PaletteFormat::Json)? + "\n",
+    ExportFormat::Toml => doc.to_string_as(PaletteFormat::Toml)?,
+    ExportFormat::Yaml => doc.to_string_as(PaletteFormat::Yaml)?,
+  };
+  Ok(s)
+}
+
+/// Exports the semantic roles (`fg`, `bg`, `accent`, ...) instead of the slots.
+pub fn export_roles(doc: &PaletteDocument, format: ExportFormat) -> Result<String> {
+  let resolved = doc.roles.resolved(&doc.colors);
+  let named = resolved.iter().map(|(role, color)| (role.name(), color));
+  let structured = || RolesDocument { name: &doc.name, roles: resolved.iter().cloned().collect() };
+  let s = match format {
+    ExportFormat::Hex => hex_lines(named),
+    ExportFormat::Css => css_properties(named),
+    ExportFormat::Json => serde_json::to_string_pretty(&structured())? + "\n",
+    ExportFormat::Toml => toml::to_string(&structured())?,
+    ExportFormat::Yaml => serde_yaml::to_string(&structured())?,
+  };
+  Ok(s)
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::colors::{roles::{RoleSource, Roles}, Colors, Slot};
+
+  fn test_document() -> PaletteDocument {
+    PaletteDocument::new("test", Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(144, 72, 93),
+      ColorRGB::new(26, 97, 127),
+      ColorRGB::new(72, 220, 3),
+    ]))
+  }
+
+  #[test]
+  fn test_export_hex() -> Result<()> {
+    let s = export_palette(&test_document(), ExportFormat::Hex)?;
+    assert_eq!(s.lines().next(), Some("background  #202020"));
+    assert_eq!(s.lines().count(), 5);
+
+    // names of any length stay apart from their color
+    let colors = Colors::new(vec![Slot::new("background", ColorRGB::new(32, 32, 32)), Slot::new("bright_magenta", ColorRGB::new(255, 0, 255))]).unwrap();
+    let doc = PaletteDocument::new("terminal", colors);
+    let s = export_palette(&doc, ExportFormat::Hex)?;
+    assert_eq!(s.lines().next(), Some("background     #202020"));
+    assert_eq!(s.lines().last(), Some("bright_magenta #FF00FF"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_export_css() -> Result<()> {
+    let s = export_palette(&test_document(), ExportFormat::Css)?;
+    assert!(s.starts_with(":root {\n"));
+    assert!(s.contains("  --color-b: #90485d;\n"));
+    Ok(())
+  }
+
+  #[test]
+  fn test_export_roles() -> Result<()> {
+    let mut roles = Roles::default();
+    roles.set(Role::Accent, RoleSource::new("color_c", 0));
+    let doc = test_document().with_roles(roles);
+    let s = export_roles(&doc, ExportFormat::Hex)?;
+    assert_eq!(s.lines().count(), Role::ALL.len());
+    assert_eq!(s.lines().next(), Some("fg          #FFFFFF"));
+    assert!(s.contains("accent      #1A617F\n"));
+    let s = export_roles(&doc, ExportFormat::Css)?;
+    assert!(s.contains("  --surface: #363636;\n"));
+    let s = export_roles(&doc, ExportFormat::Toml)?;
+    assert!(s.starts_with("name = \"test\"\n\n[roles]\nfg = \"#FFFFFF\"\nbg = \"#202020\"\n"));
+    let value: serde_json::Value = serde_json::from_str(&export_roles(&doc, ExportFormat::Json)?)?;
+    assert_eq!(value["roles"]["accent"], "#1A617F");
+    let value: serde_json::Value = serde_yaml::from_str(&export_roles(&doc, ExportFormat::Yaml)?)?;
+    assert_eq!(value["roles"]["bg"], "#202020");
+    Ok(())
+  }
+}
@@ -5,12 +5,16 @@
 pub mod action;
 pub mod app;
 pub mod cli;
+pub mod commands;
 pub mod components;
 pub mod config;
 pub mod mode;
 pub mod tui;
 pub mod utils;
 pub mod colors;
+pub mod palettes;
+pub mod export;
+pub mod terminal;
 
 use clap::Parser;
 use cli::Cli;
@@ -27,7 +31,19 @@ async fn tokio_main() -> Result<()> {
   initialize_panic_handler()?;
 
   let args = Cli::parse();
-  let mut app = App::new(args.tick_rate, args.frame_rate)?;
+  if let Some(command) = &args.command {
+    return commands::run(command, &mut std::io::stdout());
+  }
+  let palette = args.startup_palette()?;
+  let color_depth = args.color_depth.unwrap_or_else(terminal::detect_color_depth);
+  let terminal_palette = match terminal::read_palette() {
+    Ok(palette) => Some(palette.to_document()),
+    Err(e) => {
+      log::info!("Could not read the terminal palette: {e}");
+      None
+    },
+  };
+  let mut app = App::new(args.tick_rate, args.frame_rate, palette, color_depth, terminal_palette)?;
   app.run().await?;
 
   Ok(())
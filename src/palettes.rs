@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{eyre, Result};
+
+use crate::{colors::schema::{PaletteDocument, PaletteFormat}, utils::get_data_dir};
+
+/// Named palettes saved as JSON palette documents in a directory, by default `<data dir>/palettes`.
+#[derive(Clone, Debug)]
+pub struct PaletteStore {
+  dir: PathBuf,
+}
+
+impl Default for PaletteStore {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl PaletteStore {
+  pub fn new() -> Self {
+    Self::with_dir(get_data_dir().join("palettes"))
+  }
+
+  pub fn with_dir(dir: PathBuf) -> Self {
+    PaletteStore { dir }
+  }
+
+  pub fn dir(&self) -> &PathBuf {
+    &self.dir
+  }
+
+  fn path_for(&self, name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    Ok(self.dir.join(format!("{}.{}", name.trim(), PaletteFormat::Json.extension())))
+  }
+
+  pub fn exists(&self, name: &str) -> bool {
+    self.path_for(name).map(|p| p.exists()).unwrap_or(false)
+  }
+
+  /// Lists all readable palettes sorted by name. Unreadable files are logged and skipped.
+  pub fn list(&self) -> Result<Vec<PaletteDocument>> {
+    if !self.dir.exists() {
+      return Ok(vec![]);
+    }
+    let mut palettes = vec![];
+    for entry in std::fs::read_dir(&self.dir)? {
+      let path = entry?.path();
+      if PaletteFormat::from_path(&path) != Some(PaletteFormat::Json) {
+        continue;
+      }
+      let Some(name) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+      match self.load(name) {
+        Ok(doc) => palettes.push(doc),
+        Err(e) => log::error!("Skipping palette {}: {:?}", path.display(), e),
+      }
+    }
+    palettes.sort_by_key(|p| p.name.to_lowercase());
+    Ok(palettes)
+  }
+
+  /// Loads a palette; its name is always the file name, whatever the document says.
+  pub fn load(&self, name: &str) -> Result<PaletteDocument> {
+    let mut doc = PaletteDocument::read(&self.path_for(name)?)?;
+    doc.name = name.trim().to_string();
+    Ok(doc)
+  }
+
+  /// Saves the palette under its name, overwriting an existing palette of the same name.
+  pub fn save(&self, doc: &PaletteDocument) -> Result<()> {
+    let path = self.path_for(&doc.name)?;
+    std::fs::create_dir_all(&self.dir)?;
+    let mut doc = doc.clone();
+    doc.name = doc.name.trim().to_string();
+    doc.write(&path)
+  }
+
+  pub fn delete(&self, name: &str) -> Result<()> {
+    let path = self.path_for(name)?;
+    std::fs::remove_file(&path).map_err(|e| eyre!("Could not delete {}: {}", path.display(), e))?;
+    Ok(())
+  }
+
+  /// Resolves a command line argument to a palette: a path to a palette file, or the name of a saved palette.
+  pub fn resolve(&self, arg: &str) -> Result<PaletteDocument> {
+    let path = Path::new(arg);
+    if PaletteFormat::from_path(path).is_some() && path.exists() {
+      return PaletteDocument::read(path);
+    }
+    if !self.exists(arg) {
+      return Err(eyre!("`{}` is neither a palette file nor a saved palette in {}", arg, self.dir.display()));
+    }
+    self.load(arg)
+  }
+
+  /// Renames a palette, refusing to overwrite an existing one.
+  pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+    let mut doc = self.load(from)?;
+    if self.exists(to) {
+      return Err(eyre!("Palette `{}` already exists", to.trim()));
+    }
+    doc.name = to.to_string();
+    self.save(&doc)?;
+    self.delete(from)
+  }
+}
+
+/// Palette names become file names, so only allow a safe subset of characters.
+pub fn validate_name(name: &str) -> Result<()> {
+  let name = name.trim();
+  if name.is_empty() {
+    return Err(eyre!("Palette name is empty"));
+  }
+  if let Some(c) = name.chars().find(|c| !(c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))) {
+    return Err(eyre!("Invalid character `{}` in palette name", c));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::colors::{ColorRGB, Colors};
+
+  fn test_store(test: &str) -> PaletteStore {
+    let dir = std::env::temp_dir().join(format!("termcolors-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    PaletteStore::with_dir(dir)
+  }
+
+  fn test_colors() -> Colors {
+    Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(144, 72, 93),
+      ColorRGB::new(26, 97, 127),
+      ColorRGB::new(72, 220, 3),
+    ])
+  }
+
+  #[test]
+  fn test_save_load() -> Result<()> {
+    let store = test_store("save-load");
+    store.save(&PaletteDocument::new("dark theme", test_colors()))?;
+    let doc = store.load("dark theme")?;
+    assert_eq!(doc.name, "dark theme");
+    assert_eq!(doc.colors, test_colors());
+    std::fs::remove_dir_all(store.dir())?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_list_rename_delete() -> Result<()> {
+    let store = test_store("list-rename-delete");
+    assert!(store.list()?.is_empty());
+    store.save(&PaletteDocument::new("b", test_colors()))?;
+    store.save(&PaletteDocument::new("a", test_colors()))?;
+    let names: Vec<String> = store.list()?.into_iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+
+    assert!(store.rename("a", "b").is_err());
+    store.rename("a", "c")?;
+    assert!(!store.exists("a"));
+    assert!(store.exists("c"));
+
+    store.delete("b")?;
+    let names: Vec<String> = store.list()?.into_iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["c".to_string()]);
+    std::fs::remove_dir_all(store.dir())?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_resolve() -> Result<()> {
+    let store = test_store("resolve");
+    store.save(&PaletteDocument::new("saved", test_colors()))?;
+    assert_eq!(store.resolve("saved")?.colors, test_colors());
+    let file = store.dir().join("saved.json");
+    assert_eq!(store.resolve(file.to_str().unwrap())?.colors, test_colors());
+    assert!(store.resolve("missing").is_err());
+    std::fs::remove_dir_all(store.dir())?;
+    Ok(())
+  }
+
+  #[test]
+  fn test_invalid_names() {
+    assert!(validate_name("").is_err());
+    assert!(validate_name("   ").is_err());
+    assert!(validate_name("../evil").is_err());
+    assert!(validate_name("my palette_2-b").is_ok());
+  }
+}
@@ -0,0 +1,438 @@
+//! Capabilities of the terminal: its color depth from `COLORTERM` and `TERM`, and queries the terminal answers.
+//!
+//! A query is followed by a primary device attributes request (DA1), which every terminal answers,
+//! so terminals that ignore the query do not stall until the timeout.
+//! The palette is read with OSC 4 (the 16 base colors), OSC 10 (foreground), OSC 11 (background) and OSC 12 (cursor),
+//! answered as `ESC ] 4 ; index ; rgb:rrrr/gggg/bbbb ST`. The same sequences with a color instead of `?` set it,
+//! OSC 104, 110, 111 and 112 reset it to the terminal's configured value.
+
+use std::{fs::File, time::Duration};
+
+use color_eyre::eyre::{eyre, Result};
+
+use crate::colors::{ansi::{self, ColorDepth}, schema::PaletteDocument, ColorRGB, Colors, Slot};
+
+/// How long to wait for the terminal to answer a query.
+pub const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Color depth as announced by the environment.
+pub fn depth_from_env(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+  let colorterm = colorterm.unwrap_or_default().to_lowercase();
+  let term = term.unwrap_or_default().to_lowercase();
+  if matches!(colorterm.as_str(), "truecolor" | "24bit") || term.contains("direct") || term.contains("truecolor") || term.ends_with("24bit") {
+    ColorDepth::TrueColor
+  } else if term.contains("256color") {
+    ColorDepth::Ansi256
+  } else {
+    ColorDepth::Ansi16
+  }
+}
+
+/// Color depth from `COLORTERM` and `TERM`; if they do not announce truecolor the terminal is asked.
+pub fn detect_color_depth() -> ColorDepth {
+  let depth = depth_from_env(std::env::var("COLORTERM").ok().as_deref(), std::env::var("TERM").ok().as_deref());
+  if depth == ColorDepth::TrueColor {
+    return depth;
+  }
+  match open_tty().and_then(|tty| supports_truecolor(&tty)) {
+    Ok(true) => {ColorDepth::TrueColor},
+    Ok(false) => {depth},
+    Err(e) => {
+      log::info!("Could not query the color depth: {e}");
+      depth
+    },
+  }
+}
+
+/// Sets a truecolor background and asks for the current graphic rendition (DECRQSS),
+/// a terminal without truecolor reports a different or no color.
+pub fn supports_truecolor(tty: &File) -> Result<bool> {
+  let response = query(tty, "\x1b[48;2;1;2;3m\x1bP$qm\x1b\\\x1b[0m", QUERY_TIMEOUT)?;
+  Ok(response.contains("1:2:3") || response.contains("1;2;3"))
+}
+
+/// Colors the terminal is using.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TerminalPalette {
+  pub foreground: ColorRGB,
+  pub background: ColorRGB,
+  pub cursor: Option<ColorRGB>, // not every terminal reports it
+  pub ansi: [ColorRGB; 16], // base colors the terminal did not report keep the xterm default
+}
+
+/// Slot names of the 16 base colors.
+pub const ANSI_NAMES: [&str; 16] = [
+  "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+  "bright_black", "bright_red", "bright_green", "bright_yellow", "bright_blue", "bright_magenta", "bright_cyan", "bright_white",
+];
+
+/// Base colors the foregrounds of a palette without base color names are applied to, after the first foreground:
+/// red, blue, green, yellow, magenta and cyan, so B, C and highlight of a five color palette become red, blue and green.
+const FALLBACK_ANSI: [usize; 6] = [1, 4, 2, 3, 5, 6];
+
+impl TerminalPalette {
+  /// As a palette named `terminal`: background, foreground, the 16 base colors by name and the cursor if it was reported.
+  pub fn to_document(&self) -> PaletteDocument {
+    let mut slots = vec![Slot::new("background", self.background.clone()), Slot::new("foreground", self.foreground.clone())];
+    slots.extend(ANSI_NAMES.iter().zip(&self.ansi).map(|(name, color)| Slot::new(name, color.clone())));
+    if let Some(cursor) = &self.cursor {
+      slots.push(Slot::new("cursor", cursor.clone()));
+    }
+    PaletteDocument::new("terminal", Colors::new(slots).expect("the terminal palette has unique names"))
+  }
+
+  /// Sets the terminal's colors back to these.
+  pub fn restore_sequence(&self) -> String {
+    let mut sequence = format!("\x1b]10;{}\x1b\\\x1b]11;{}\x1b\\", osc_color(&self.foreground), osc_color(&self.background));
+    match &self.cursor {
+      Some(cursor) => {sequence.push_str(&format!("\x1b]12;{}\x1b\\", osc_color(cursor)));},
+      None => {sequence.push_str("\x1b]112\x1b\\");},
+    }
+    for (index, color) in self.ansi.iter().enumerate() {
+      sequence.push_str(&format!("\x1b]4;{index};{}\x1b\\", osc_color(color)));
+    }
+    sequence
+  }
+}
+
+/// Resets the base colors, foreground, background and cursor to the terminal's configured values.
+pub const RESET_SEQUENCE: &str = "\x1b]104\x1b\\\x1b]110\x1b\\\x1b]111\x1b\\\x1b]112\x1b\\";
+
+/// Themes the terminal with a palette, the reverse of [`TerminalPalette::to_document`]: the background from the first slot,
+/// the foreground from the `foreground` slot or the second one, the cursor from `cursor` or `highlight`,
+/// and the base colors from the slots named after them. A palette without such names sets red, blue, green, yellow,
+/// magenta and cyan (normal and bright) from its foregrounds after the first, in order.
+/// Translucent colors should be composited first.
+pub fn apply_sequence(colors: &Colors) -> String {
+  let foreground = colors.by_name("foreground").unwrap_or(colors.color(1));
+  let cursor = colors.by_name("cursor").or(colors.by_name("highlight")).unwrap_or(foreground);
+  let mut sequence = format!("\x1b]10;{}\x1b\\\x1b]11;{}\x1b\\\x1b]12;{}\x1b\\", osc_color(foreground), osc_color(colors.background()), osc_color(cursor));
+  let named: Vec<(usize, &ColorRGB)> = ANSI_NAMES.iter().enumerate().filter_map(|(index, name)| Some((index, colors.by_name(name)?))).collect();
+  if !named.is_empty() {
+    for (index, color) in named {
+      sequence.push_str(&format!("\x1b]4;{index};{}\x1b\\", osc_color(color)));
+    }
+    return sequence;
+  }
+  for (index, color) in FALLBACK_ANSI.iter().zip(colors.foregrounds().skip(1)) {
+    sequence.push_str(&format!("\x1b]4;{index};{}\x1b\\\x1b]4;{};{}\x1b\\", osc_color(color), index + 8, osc_color(color)));
+  }
+  sequence
+}
+
+fn osc_color(color: &ColorRGB) -> String {
+  format!("rgb:{:02x}/{:02x}/{:02x}", color.r, color.g, color.b)
+}
+
+/// Asks the terminal on `/dev/tty` for its palette.
+pub fn read_palette() -> Result<TerminalPalette> {
+  query_palette(&open_tty()?, QUERY_TIMEOUT)
+}
+
+/// Asks for the foreground, background, cursor and the 16 base colors in one query.
+pub fn query_palette(tty: &File, timeout: Duration) -> Result<TerminalPalette> {
+  let mut request = String::from("\x1b]10;?\x1b\\\x1b]11;?\x1b\\\x1b]12;?\x1b\\");
+  for index in 0..16 {
+    request.push_str(&format!("\x1b]4;{index};?\x1b\\"));
+  }
+  parse_palette(&query(tty, &request, timeout)?)
+}
+
+/// Collects the OSC 4, 10, 11 and 12 replies, foreground and background have to be among them.
+pub fn parse_palette(response: &str) -> Result<TerminalPalette> {
+  let mut foreground = None;
+  let mut background = None;
+  let mut cursor = None;
+  let mut ansi = ansi::ANSI16.map(|(r, g, b)| ColorRGB::new(r, g, b));
+  for reply in osc_replies(response) {
+    let mut fields = reply.splitn(3, ';');
+    match (fields.next(), fields.next(), fields.next()) {
+      (Some("10"), Some(spec), None) => {foreground = parse_osc_color(spec);},
+      (Some("11"), Some(spec), None) => {background = parse_osc_color(spec);},
+      (Some("12"), Some(spec), None) => {cursor = parse_osc_color(spec);},
+      (Some("4"), Some(index), Some(spec)) => {
+        let (Ok(index), Some(color)) = (index.parse::<usize>(), parse_osc_color(spec)) else {continue};
+        if let Some(slot) = ansi.get_mut(index) {
+          *slot = color;
+        }
+      },
+      _ => {},
+    }
+  }
+  match (foreground, background) {
+    (Some(foreground), Some(background)) => {Ok(TerminalPalette { foreground, background, cursor, ansi })},
+    _ => {Err(eyre!("the terminal did not report its colors"))},
+  }
+}
+
+/// Bodies of the OSC sequences in `response`, terminated by BEL or ST.
+fn osc_replies(response: &str) -> Vec<&str> {
+  let mut replies = vec![];
+  let mut rest = response;
+  while let Some(start) = rest.find("\x1b]") {
+    rest = &rest[start + 2..];
+    let Some(end) = rest.find(['\x07', '\x1b']) else {break};
+    replies.push(&rest[..end]);
+    rest = &rest[end..];
+  }
+  replies
+}
+
+/// Parses an X11 color spec as terminals answer it: `rgb:r/g/b` or `rgba:r/g/b/a` with 1 - 4 hex digits per channel.
+pub fn parse_osc_color(spec: &str) -> Option<ColorRGB> {
+  let (channels, count) = if let Some(rest) = spec.strip_prefix("rgba:") {
+    (rest, 4)
+  } else {
+    (spec.strip_prefix("rgb:")?, 3)
+  };
+  let channels: Vec<u8> = channels.split('/').map(scale_channel).collect::<Option<_>>()?;
+  match channels[..] {
+    [r, g, b] if count == 3 => {Some(ColorRGB::new(r, g, b))},
+    [r, g, b, a] if count == 4 => {Some(ColorRGB::from_rgba(r, g, b, a))},
+    _ => {None},
+  }
+}
+
+/// `f`, `ff`, `fff` and `ffff` are all full intensity.
+fn scale_channel(hex: &str) -> Option<u8> {
+  if hex.is_empty() || hex.len() > 4 {
+    return None;
+  }
+  let value = u32::from_str_radix(hex, 16).ok()?;
+  let max = (1u32 << (4 * hex.len())) - 1;
+  Some(((value * 255 + max / 2) / max) as u8)
+}
+
+#[cfg(unix)]
+pub fn open_tty() -> Result<File> {
+  Ok(std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty")?)
+}
+
+#[cfg(not(unix))]
+pub fn open_tty() -> Result<File> {
+  Err(eyre!("terminal queries are only supported on unix"))
+}
+
+/// Writes `request` to the terminal and returns what it answered before the DA1 reply.
+/// The terminal is in raw mode while waiting, so the answer is not echoed.
+#[cfg(unix)]
+pub fn query(tty: &File, request: &str, timeout: Duration) -> Result<String> {
+  use std::{io::{Read, Write}, os::fd::AsRawFd, time::Instant};
+
+  let fd = tty.as_raw_fd();
+  let _raw = RawMode::enable(fd)?;
+  let mut writer = tty;
+  writer.write_all(request.as_bytes())?;
+  writer.write_all(b"\x1b[c")?;
+  writer.flush()?;
+
+  let deadline = Instant::now() + timeout;
+  let mut response = vec![];
+  loop {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+      return Err(eyre!("the terminal did not answer within {} ms", timeout.as_millis()));
+    }
+    let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis().max(1) as libc::c_int) };
+    if ready < 0 {
+      let err = std::io::Error::last_os_error();
+      if err.kind() == std::io::ErrorKind::Interrupted {continue}
+      return Err(err.into());
+    }
+    if ready == 0 {continue}
+    let mut buf = [0u8; 256];
+    let mut reader = tty;
+    let read = reader.read(&mut buf)?;
+    if read == 0 {
+      return Err(eyre!("the terminal closed while answering"));
+    }
+    response.extend_from_slice(&buf[..read]);
+    if let Some(start) = find_device_attributes(&response) {
+      response.truncate(start);
+      return Ok(String::from_utf8_lossy(&response).into_owned());
+    }
+  }
+}
+
+#[cfg(not(unix))]
+pub fn query(_tty: &File, _request: &str, _timeout: Duration) -> Result<String> {
+  Err(eyre!("terminal queries are only supported on unix"))
+}
+
+/// Start of a complete DA1 reply, `ESC [ ? 6 4 ; 1 ; 2 c`.
+fn find_device_attributes(response: &[u8]) -> Option<usize> {
+  (0..response.len()).find(|&start| {
+    let Some(rest) = response[start..].strip_prefix(b"\x1b[?") else {return false};
+    let params = rest.iter().take_while(|b| b.is_ascii_digit() || **b == b';').count();
+    rest.get(params) == Some(&b'c')
+  })
+}
+
+/// Puts a terminal into raw mode and restores its settings when dropped.
+#[cfg(unix)]
+struct RawMode {
+  fd: std::os::fd::RawFd,
+  original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawMode {
+  fn enable(fd: std::os::fd::RawFd) -> Result<Self> {
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    let mut raw = original;
+    unsafe { libc::cfmakeraw(&mut raw) };
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(Self { fd, original })
+  }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+  fn drop(&mut self) {
+    unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.original) };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn test_depth_from_env() {
+    assert_eq!(depth_from_env(Some("truecolor"), Some("xterm-256color")), ColorDepth::TrueColor);
+    assert_eq!(depth_from_env(Some("24bit"), None), ColorDepth::TrueColor);
+    assert_eq!(depth_from_env(None, Some("xterm-direct")), ColorDepth::TrueColor);
+    assert_eq!(depth_from_env(None, Some("xterm-256color")), ColorDepth::Ansi256);
+    assert_eq!(depth_from_env(Some(""), Some("screen-256color")), ColorDepth::Ansi256);
+    assert_eq!(depth_from_env(None, Some("xterm")), ColorDepth::Ansi16);
+    assert_eq!(depth_from_env(None, Some("linux")), ColorDepth::Ansi16);
+    assert_eq!(depth_from_env(None, None), ColorDepth::Ansi16);
+  }
+
+  #[test]
+  fn test_find_device_attributes() {
+    assert_eq!(find_device_attributes(b"\x1bP1$r48:2::1:2:3m\x1b\\\x1b[?64;1;2c"), Some(19));
+    assert_eq!(find_device_attributes(b"\x1b[?1;2c"), Some(0));
+    assert_eq!(find_device_attributes(b"\x1b[?64;1;2"), None);
+    assert_eq!(find_device_attributes(b"\x1b[1;2c"), None);
+  }
+
+  #[test]
+  fn test_parse_osc_color() {
+    assert_eq!(parse_osc_color("rgb:ffff/8080/0000"), Some(ColorRGB::new(255, 128, 0)));
+    assert_eq!(parse_osc_color("rgb:f/8/0"), Some(ColorRGB::new(255, 136, 0)));
+    assert_eq!(parse_osc_color("rgb:20/20/20"), Some(ColorRGB::new(32, 32, 32)));
+    assert_eq!(parse_osc_color("rgba:ffff/ffff/ffff/8080"), Some(ColorRGB::from_rgba(255, 255, 255, 128)));
+    assert_eq!(parse_osc_color("rgb:ffff/ffff"), None);
+    assert_eq!(parse_osc_color("rgb:fffff/0/0"), None);
+    assert_eq!(parse_osc_color("#ffffff"), None);
+  }
+
+  #[test]
+  fn test_parse_palette() {
+    let response = "\x1b]10;rgb:ffff/ffff/ffff\x07\x1b]11;rgb:2020/2020/2020\x1b\\\x1b]4;1;rgb:cccc/2424/1d1d\x1b\\";
+    let palette = parse_palette(response).unwrap();
+    assert_eq!(palette.foreground, ColorRGB::new(255, 255, 255));
+    assert_eq!(palette.background, ColorRGB::new(32, 32, 32));
+    assert_eq!(palette.ansi[1], ColorRGB::new(204, 36, 29));
+    assert_eq!(palette.ansi[2], ColorRGB::new(0, 205, 0));
+    assert_eq!(palette.cursor, None);
+    assert!(parse_palette("\x1b]4;1;rgb:cccc/2424/1d1d\x1b\\").is_err());
+  }
+
+  #[test]
+  fn test_sequences() {
+    let colors = Colors::from_colors(vec![
+      ColorRGB::new(32, 32, 32),
+      ColorRGB::new(255, 255, 255),
+      ColorRGB::new(144, 72, 93),
+      ColorRGB::new(26, 97, 127),
+      ColorRGB::new(72, 220, 3),
+    ]);
+    let sequence = apply_sequence(&colors);
+    assert!(sequence.starts_with("\x1b]10;rgb:ff/ff/ff\x1b\\\x1b]11;rgb:20/20/20\x1b\\\x1b]12;rgb:48/dc/03\x1b\\"));
+    assert!(sequence.contains("\x1b]4;1;rgb:90/48/5d\x1b\\\x1b]4;9;rgb:90/48/5d\x1b\\"));
+    assert!(sequence.contains("\x1b]4;12;rgb:1a/61/7f\x1b\\"));
+    assert!(sequence.contains("\x1b]4;2;rgb:48/dc/03\x1b\\"));
+    assert!(!sequence.contains("\x1b]4;3;"));
+    // applying a terminal palette and reading it back gives the same palette
+    let palette = parse_palette(&sequence).unwrap();
+    assert_eq!(palette.cursor, Some(ColorRGB::new(72, 220, 3)));
+    let doc = palette.to_document();
+    assert_eq!(doc.colors.len(), 19);
+    assert_eq!(parse_palette(&apply_sequence(&doc.colors)).unwrap(), palette);
+    assert_eq!(parse_palette(&palette.restore_sequence()).unwrap(), palette);
+    let no_cursor = TerminalPalette { cursor: None, ..palette };
+    assert!(no_cursor.restore_sequence().contains("\x1b]112\x1b\\"));
+  }
+
+  /// A pseudo-terminal whose other end answers like a terminal with `answer`, after it saw the DA1 request.
+  #[cfg(unix)]
+  fn stand_in(answer: fn(&str) -> String) -> File {
+    use std::{ffi::CStr, io::{Read, Write}, os::fd::FromRawFd};
+
+    let (mut master, slave) = unsafe {
+      let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+      assert!(master >= 0 && libc::grantpt(master) == 0 && libc::unlockpt(master) == 0);
+      let path = CStr::from_ptr(libc::ptsname(master)).to_str().unwrap().to_string();
+      (File::from_raw_fd(master), std::fs::OpenOptions::new().read(true).write(true).open(path).unwrap())
+    };
+    std::thread::spawn(move || {
+      let mut request = vec![];
+      let mut buf = [0u8; 1024];
+      while !request.ends_with(b"\x1b[c") {
+        let Ok(read) = master.read(&mut buf) else {return};
+        if read == 0 {return}
+        request.extend_from_slice(&buf[..read]);
+      }
+      let reply = answer(&String::from_utf8_lossy(&request));
+      master.write_all(reply.as_bytes()).unwrap();
+      // keep the pty open until the reader is done
+      let _ = master.read(&mut buf);
+    });
+    slave
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_query_palette() {
+    let tty = stand_in(|request| {
+      let mut reply = String::new();
+      for (query, color) in [("10", "rgb:d0d0/d0d0/d0d0"), ("11", "rgb:1c1c/1c1c/1c1c"), ("12", "rgb:ffff/0000/0000"), ("4;4", "rgb:3434/6565/a4a4")] {
+        if request.contains(&format!("\x1b]{query};?")) {
+          reply.push_str(&format!("\x1b]{query};{color}\x1b\\"));
+        }
+      }
+      reply + "\x1b[?64;1;2c"
+    });
+    let palette = query_palette(&tty, Duration::from_secs(5)).unwrap();
+    assert_eq!(palette.foreground, ColorRGB::new(208, 208, 208));
+    assert_eq!(palette.background, ColorRGB::new(28, 28, 28));
+    assert_eq!(palette.cursor, Some(ColorRGB::new(255, 0, 0)));
+    assert_eq!(palette.ansi[4], ColorRGB::new(52, 101, 164));
+    let doc = palette.to_document();
+    assert_eq!(doc.name, "terminal");
+    assert_eq!(doc.colors.background(), &ColorRGB::new(28, 28, 28));
+    assert_eq!(doc.colors.by_name("blue"), Some(&ColorRGB::new(52, 101, 164)));
+    assert_eq!(doc.colors.by_name("cursor"), Some(&ColorRGB::new(255, 0, 0)));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_query_palette_unanswered() {
+    // a terminal without OSC 4/10/11 only answers DA1
+    let tty = stand_in(|_| "\x1b[?1;2c".to_string());
+    assert!(query_palette(&tty, Duration::from_secs(5)).is_err());
+    // one that does not even answer DA1 runs into the timeout
+    let tty = stand_in(|_| String::new());
+    assert!(query_palette(&tty, Duration::from_millis(50)).is_err());
+  }
+}
@@ -1,5 +1,7 @@
 use std::{
+  io::Write,
   ops::{Deref, DerefMut},
+  sync::Mutex,
   time::Duration,
 };
 
@@ -21,6 +23,11 @@ use tokio::{
 };
 use tokio_util::sync::CancellationToken;
 
+use crate::{colors::Colors, terminal::{self, TerminalPalette}};
+
+/// The sequence that gives the terminal its colors back while a palette is applied, for the panic hook.
+static PENDING_RESTORE: Mutex<Option<String>> = Mutex::new(None);
+
 pub type IO = std::io::Stderr;
 pub fn io() -> IO {
   std::io::stderr()
@@ -53,6 +60,8 @@ pub struct Tui {
   pub tick_rate: f64,
   pub mouse: bool,
   pub paste: bool,
+  pub palette_applied: bool, // the terminal is themed with a palette and has to be restored
+  pub saved_palette: Option<TerminalPalette>, // the terminal's own colors, read before the first palette was applied
 }
 
 impl Tui {
@@ -65,7 +74,9 @@ impl Tui {
     let task = tokio::spawn(async {});
     let mouse = false;
     let paste = false;
-    Ok(Self { terminal, task, cancellation_token, event_rx, event_tx, frame_rate, tick_rate, mouse, paste })
+    let palette_applied = false;
+    let saved_palette = None;
+    Ok(Self { terminal, task, cancellation_token, event_rx, event_tx, frame_rate, tick_rate, mouse, paste, palette_applied, saved_palette })
   }
 
   pub fn tick_rate(mut self, tick_rate: f64) -> Self {
@@ -183,6 +194,7 @@ impl Tui {
 
   pub fn exit(&mut self) -> Result<()> {
     self.stop()?;
+    self.restore_palette()?;
     if crossterm::terminal::is_raw_mode_enabled()? {
       self.flush()?;
       if self.paste {
@@ -197,6 +209,57 @@ impl Tui {
     Ok(())
   }
 
+  /// Asks the terminal for its colors, the event reader is paused meanwhile so it does not take the answer.
+  /// While a palette is applied the terminal's own colors are returned.
+  pub fn read_palette(&mut self) -> Result<TerminalPalette> {
+    if let (true, Some(saved)) = (self.palette_applied, &self.saved_palette) {
+      return Ok(saved.clone());
+    }
+    self.stop()?;
+    let palette = terminal::read_palette();
+    self.start();
+    palette
+  }
+
+  /// Themes the terminal with `colors`, the first time its own colors are saved for [`Tui::restore_palette`].
+  pub fn apply_palette(&mut self, colors: &Colors) -> Result<()> {
+    if !self.palette_applied {
+      self.saved_palette = match self.read_palette() {
+        Ok(palette) => Some(palette),
+        Err(e) => {
+          log::info!("Could not save the terminal palette, it is reset on exit: {e}");
+          None
+        },
+      };
+      self.palette_applied = true;
+      *PENDING_RESTORE.lock().unwrap_or_else(|e| e.into_inner()) = Some(self.restore_sequence());
+    }
+    write!(io(), "{}", terminal::apply_sequence(colors))?;
+    io().flush()?;
+    Ok(())
+  }
+
+  /// Gives the terminal its saved colors back, or resets them to its configured ones if they could not be read.
+  pub fn restore_palette(&mut self) -> Result<()> {
+    if !self.palette_applied {
+      return Ok(());
+    }
+    let sequence = self.restore_sequence();
+    write!(io(), "{sequence}")?;
+    io().flush()?;
+    self.saved_palette = None;
+    self.palette_applied = false;
+    PENDING_RESTORE.lock().unwrap_or_else(|e| e.into_inner()).take();
+    Ok(())
+  }
+
+  fn restore_sequence(&self) -> String {
+    match &self.saved_palette {
+      Some(palette) => {palette.restore_sequence()},
+      None => {terminal::RESET_SEQUENCE.to_string()},
+    }
+  }
+
   pub fn cancel(&self) {
     self.cancellation_token.cancel();
   }
@@ -237,3 +300,13 @@ impl Drop for Tui {
     self.exit().unwrap();
   }
 }
+
+/// Gives the terminal its colors back if a palette is still applied. The panic hook has no access
+/// to the running [`Tui`], a fresh one does not know about the applied palette.
+pub fn restore_palette_after_panic() {
+  let pending = PENDING_RESTORE.lock().unwrap_or_else(|e| e.into_inner()).take();
+  if let Some(sequence) = pending {
+    let _ = write!(io(), "{sequence}");
+    let _ = io().flush();
+  }
+}
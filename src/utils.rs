@@ -32,6 +32,7 @@ pub fn initialize_panic_handler() -> Result<()> {
     .into_hooks();
   eyre_hook.install()?;
   std::panic::set_hook(Box::new(move |panic_info| {
+    crate::tui::restore_palette_after_panic();
     if let Ok(mut t) = crate::tui::Tui::new() {
       if let Err(r) = t.exit() {
         error!("Unable to exit Terminal: {:?}", r);